@@ -0,0 +1,146 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use num::BigInt;
+
+// A puzzle answer, whatever the type a day computes it in.
+#[derive(Debug, Clone)]
+pub enum Answer {
+    Signed(i64),
+    Unsigned(u64),
+    Big(BigInt),
+    Text(String),
+}
+
+impl Answer {
+    fn to_big(&self) -> Option<BigInt> {
+        match self {
+            Answer::Signed(n) => Some(BigInt::from(*n)),
+            Answer::Unsigned(n) => Some(BigInt::from(*n)),
+            Answer::Big(n) => Some(n.clone()),
+            Answer::Text(_) => None,
+        }
+    }
+}
+
+// Numbers compare by value, so an answer computed as isize equals the
+// same answer recorded as u64.
+impl PartialEq for Answer {
+    fn eq(&self, other: &Answer) -> bool {
+        match (self, other) {
+            (Answer::Text(l), Answer::Text(r)) => l == r,
+            (Answer::Text(_), _) | (_, Answer::Text(_)) => false,
+            _ => self.to_big() == other.to_big(),
+        }
+    }
+}
+
+impl Eq for Answer {}
+
+// Only numbers are ordered, so that "too high" and "too low" verdicts
+// carry over to other answers.
+impl PartialOrd for Answer {
+    fn partial_cmp(&self, other: &Answer) -> Option<Ordering> {
+        match (self, other) {
+            (Answer::Text(l), Answer::Text(r)) if l == r => Some(Ordering::Equal),
+            _ => Some(self.to_big()?.cmp(&other.to_big()?)),
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Signed(n) => f.pad(&n.to_string()),
+            Answer::Unsigned(n) => f.pad(&n.to_string()),
+            Answer::Big(n) => f.pad(&n.to_string()),
+            Answer::Text(s) => f.pad(s),
+        }
+    }
+}
+
+// Anything that reads as an integer becomes the narrowest numeric
+// variant holding it, everything else is kept as text.
+impl FromStr for Answer {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(n) = s.parse::<u64>() {
+            Ok(Answer::Unsigned(n))
+        } else if let Ok(n) = s.parse::<i64>() {
+            Ok(Answer::Signed(n))
+        } else if let Ok(n) = s.parse::<BigInt>() {
+            Ok(Answer::Big(n))
+        } else {
+            Ok(Answer::Text(s.to_string()))
+        }
+    }
+}
+
+macro_rules! answer_from {
+    ($variant:ident, $as:ty, $($t:ty),+) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Answer::$variant(n as $as)
+                }
+            }
+        )+
+    };
+}
+
+answer_from!(Unsigned, u64, u8, u16, u32, u64, usize);
+answer_from!(Signed, i64, i8, i16, i32, i64, isize);
+
+impl From<BigInt> for Answer {
+    fn from(n: BigInt) -> Self {
+        Answer::Big(n)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Text(s.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Answer;
+    use num::BigInt;
+
+    #[test]
+    fn test_parse_and_display() {
+        for s in ["0", "6440", "-42", "123456789012345678901234567890", "EFGH"] {
+            assert_eq!(s, s.parse::<Answer>().unwrap().to_string());
+        }
+        assert_eq!(Answer::Unsigned(6440), "6440".parse().unwrap());
+        assert_eq!(Answer::Signed(-42), "-42".parse().unwrap());
+        assert_eq!(Answer::Text("EFGH".to_string()), "EFGH".parse().unwrap());
+    }
+
+    #[test]
+    fn test_numeric_equality() {
+        assert_eq!(Answer::from(67363isize), Answer::from(67363u32));
+        assert_eq!(Answer::from(BigInt::from(7)), Answer::from(7usize));
+        assert_ne!(Answer::from(-1i64), Answer::from(1u64));
+        assert_ne!(Answer::from("7"), Answer::from(7u64));
+    }
+
+    #[test]
+    fn test_numeric_order() {
+        assert!(Answer::from(-3i64) < Answer::from(2u64));
+        assert!(Answer::from(BigInt::from(u64::MAX) * 2) > Answer::from(u64::MAX));
+        assert!(Answer::from(5u8) >= Answer::from(5i64));
+        assert_eq!(None, Answer::from("ABC").partial_cmp(&Answer::from(1u64)));
+        assert_eq!(None, Answer::from("ABC").partial_cmp(&Answer::from("ABD")));
+    }
+}
@@ -0,0 +1,87 @@
+use std::path::Path;
+use std::{fs, io};
+
+use aoc23::answer::Answer;
+use aoc23::solution::Part;
+
+use aoc23::toml::{Document, Value};
+
+// Accepted answers, kept as a [day<n>] table per day with part1 and
+// part2 keys. Numbers too big for a TOML integer are kept as strings.
+#[derive(Debug, Default)]
+pub struct Answers {
+    doc: Document,
+}
+
+fn table(day: u32) -> String {
+    format!("day{}", day)
+}
+
+fn key(part: Part) -> String {
+    format!("part{}", part)
+}
+
+impl Answers {
+    // A missing file is just an empty set of answers.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Answers::default()),
+            Err(e) => return Err(e),
+        };
+        let doc = Document::parse(&text).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), e),
+            )
+        })?;
+        Ok(Answers { doc })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.doc.to_string())
+    }
+
+    pub fn get(&self, day: u32, part: Part) -> Option<Answer> {
+        match self.doc.get(&table(day), &key(part))? {
+            Value::Int(n) => Some(Answer::from(*n)),
+            Value::Str(s) => s.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn set(&mut self, day: u32, part: Part, answer: &Answer) {
+        let value = match answer {
+            Answer::Signed(n) => Value::Int(*n),
+            Answer::Unsigned(n) if *n <= i64::MAX as u64 => Value::Int(*n as i64),
+            _ => Value::Str(answer.to_string()),
+        };
+        self.doc.set(&table(day), &key(part), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Answers;
+    use aoc23::answer::Answer;
+    use aoc23::solution::Part;
+    use num::BigInt;
+
+    #[test]
+    fn test_round_trip() {
+        let mut answers = Answers::default();
+        let big = Answer::from("98584749701530000000000".parse::<BigInt>().unwrap());
+        answers.set(2, Part::Two, &Answer::from(67363isize));
+        answers.set(8, Part::Two, &big);
+        answers.set(8, Part::One, &Answer::from(u64::MAX));
+
+        let text = answers.doc.to_string();
+        let answers = Answers {
+            doc: aoc23::toml::Document::parse(&text).unwrap(),
+        };
+        assert_eq!(Some(Answer::from(67363u64)), answers.get(2, Part::Two));
+        assert_eq!(Some(big), answers.get(8, Part::Two));
+        assert_eq!(Some(Answer::from(u64::MAX)), answers.get(8, Part::One));
+        assert_eq!(None, answers.get(2, Part::One));
+    }
+}
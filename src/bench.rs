@@ -0,0 +1,176 @@
+use std::fmt;
+use std::hint::black_box;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use aoc23::params::Params;
+use aoc23::solution::{Part, Solver};
+
+use crate::runner;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Parse,
+    Solve(Part),
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::Parse => f.pad("parse"),
+            Stage::Solve(part) => f.pad(&format!("part{}", part)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub iters: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    pub fn new(mut samples: Vec<Duration>) -> Self {
+        samples.sort();
+        let n = samples.len();
+        let secs: Vec<f64> = samples.iter().map(Duration::as_secs_f64).collect();
+        let mean = secs.iter().sum::<f64>() / n as f64;
+        let var = secs.iter().map(|s| (s - mean) * (s - mean)).sum::<f64>() / n as f64;
+        let median = if n % 2 == 1 {
+            samples[n / 2]
+        } else {
+            (samples[n / 2 - 1] + samples[n / 2]) / 2
+        };
+        Stats {
+            iters: n,
+            min: samples[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(var.sqrt()),
+        }
+    }
+}
+
+pub struct Measurement {
+    pub year: u32,
+    pub day: u32,
+    pub stage: Stage,
+    pub stats: Stats,
+}
+
+pub struct BenchOpts {
+    pub warmup: usize,
+    pub iters: usize,
+    pub timeout: Duration,
+}
+
+fn sample<T>(opts: &BenchOpts, mut f: impl FnMut() -> T) -> Stats {
+    for _ in 0..opts.warmup {
+        black_box(f());
+    }
+    let samples = (0..opts.iters)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .collect();
+    Stats::new(samples)
+}
+
+// Runs a part once in isolation, to only benchmark the ones that
+// succeed in time.
+fn succeeds(
+    solver: &'static dyn Solver,
+    input: &str,
+    params: &Params,
+    part: Part,
+    timeout: Duration,
+) -> bool {
+    let _quiet = runner::QuietWorkers::install();
+    runner::solve_part(solver, Arc::from(input), params.clone(), part, timeout)
+        .0
+        .is_ok()
+}
+
+// Time parsing and every part of a day that solves without failing;
+// the failing ones are reported on stderr and skipped.
+pub fn bench_day(
+    solver: &'static dyn Solver,
+    input: &str,
+    params: &Params,
+    parts: &[Part],
+    opts: &BenchOpts,
+) -> Vec<Measurement> {
+    let (year, day) = (solver.year(), solver.day());
+    let model = match solver.parse_with(input, params) {
+        Ok(model) => model,
+        Err(e) => {
+            eprintln!("skipping day {}: {}", day, e);
+            return vec![];
+        }
+    };
+
+    let mut measurements = vec![Measurement {
+        year,
+        day,
+        stage: Stage::Parse,
+        stats: sample(opts, || solver.parse_with(input, params)),
+    }];
+    for &part in parts {
+        if !succeeds(solver, input, params, part, opts.timeout) {
+            eprintln!("skipping day {} part {}: it does not solve", day, part);
+            continue;
+        }
+        measurements.push(Measurement {
+            year,
+            day,
+            stage: Stage::Solve(part),
+            stats: sample(opts, || solver.solve(model.as_ref(), part)),
+        });
+    }
+    measurements
+}
+
+pub fn print_table(measurements: &[Measurement]) {
+    println!(
+        "{:>3}  {:<5}  {:>6}  {:>10}  {:>10}  {:>10}  {:>10}",
+        "day", "stage", "iters", "min", "median", "mean", "stddev"
+    );
+    for m in measurements {
+        let s = &m.stats;
+        println!(
+            "{:>3}  {:<5}  {:>6}  {:>10}  {:>10}  {:>10}  {:>10}",
+            m.day,
+            m.stage,
+            s.iters,
+            format!("{:.2?}", s.min),
+            format!("{:.2?}", s.median),
+            format!("{:.2?}", s.mean),
+            format!("{:.2?}", s.stddev),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Stats;
+    use std::time::Duration;
+
+    #[test]
+    fn test_stats() {
+        let ms = |n| Duration::from_millis(n);
+        let stats = Stats::new(vec![ms(4), ms(2), ms(8), ms(6)]);
+        assert_eq!(4, stats.iters);
+        assert_eq!(ms(2), stats.min);
+        assert_eq!(ms(5), stats.median);
+        assert_eq!(ms(5), stats.mean);
+        assert!((stats.stddev.as_secs_f64() - 5f64.sqrt() / 1000.0).abs() < 1e-9);
+
+        let stats = Stats::new(vec![ms(3), ms(1), ms(2)]);
+        assert_eq!(ms(2), stats.median);
+    }
+}
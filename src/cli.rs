@@ -0,0 +1,952 @@
+use std::fmt;
+use std::time::Duration;
+
+use aoc23::registry;
+use aoc23::solution::Part;
+
+use crate::config;
+use crate::format::Format;
+
+pub const USAGE: &str = "\
+usage: aoc23 [--year <year>] <command> [options]
+
+    --year <year>           puzzle year of every command below, which may
+                            also come after the command (default: 2023)
+
+commands:
+    list                    list the implemented days of the year
+    run                     solve puzzles
+    verify                  check answers against data/<year>/answers.toml
+    bench                   time parsing and solving
+    compare                 compare the last two recorded benchmarks of the year
+    watch                   re-run a day whenever its source or input changes
+    new-day <n>             start a new day from a template
+    fetch <n>               download the puzzle input of a day
+    submit <n> <1|2>        send the answer of a part to the puzzle site
+    gen <n>                 print a random input for a day
+    difftest <n>            check a day against its naive reference
+    lint <n> [<path|->]     report every problem in an input without solving
+                            it (default: data/<year>/day<n>)
+    repl <n> [<path>]       parse an input once and answer queries about it
+                            read from stdin (default: data/<year>/day<n>)
+
+run options:
+    --day <n>               day to solve
+    --all                   solve every implemented day
+    --part <1|2>            solve only one part (default: both)
+    --input <path|->        puzzle input, - for stdin
+                            (default: data/<year>/day<n>)
+    --example               use the inputs under examples/<year>/day<n>
+    --format <text|json|csv>
+                            output format (default: text)
+    --timeout <seconds>     give up on a part after this long (default: 10)
+    --jobs <n>              parts to solve at once (default: one per CPU)
+    --explain               trace how each part got its answer (text only)
+
+verify options:
+    --day <n>               day to check (default: every day)
+    --timeout <seconds>     as for run
+    --answers <path>        recorded answers (default: data/<year>/answers.toml)
+    --record                record the answers of MISSING parts
+
+bench options:
+    --day, --all, --part, --input, --example, --timeout as for run
+                            (default: --all)
+    --warmup <n>            untimed runs before measuring (default: 5)
+    --iters <n>             timed runs per stage (default: 100)
+    --record                append the timings to the history file
+    --history <path>        history file (default: bench_history.tsv)
+
+compare options:
+    --threshold <percent>   slowdown to flag (default: 10)
+    --baseline <run>        compare against this run instead of the one before last
+    --history <path>        history file (default: bench_history.tsv)
+
+watch options:
+    --day <n>               day to watch
+    --example               solve the inputs under examples/<year>/day<n>
+
+new-day options:
+    --title <title>         the puzzle title (default: Day <n>)
+
+fetch options:
+    --base-url <url>        puzzle site (default: https://adventofcode.com)
+
+submit options:
+    --base-url <url>        as for fetch
+    --answers <path>        recorded answers (default: data/<year>/answers.toml)
+
+gen options:
+    --seed <n>              seed to generate from (default: from the clock)
+    --size <n>              rough size of the input, in lines or items
+                            (default: 100)
+
+difftest options:
+    --seed <n>              first seed (default: 0)
+    --count <n>             inputs to check (default: 1000)
+    --size <n>              largest input size, going round from 1
+                            (default: 20)
+
+environment:
+    AOC_DATA_DIR            directory holding the <year>/day<n> inputs
+                            (default: data)
+    AOC_SESSION             session token for fetch and submit
+                            (default: read from .aoc-session)
+    AOC_BASE_URL            puzzle site, as --base-url
+    AOC_CONFIG              config file (default: aoc.toml, if there is one)
+
+The config file can change the defaults of --format, --timeout,
+--warmup, --iters and --base-url, the data directory, the session file
+and puzzle parameters such as day 2's bag. Flags and environment
+variables override it.
+
+Inputs, answers and examples of a year live under data/<year>/ and
+examples/<year>/. The 2023 ones may also stay directly under data/ and
+examples/, which is where they are looked for when there is no 2023
+directory.";
+
+#[derive(Debug, PartialEq)]
+pub struct UsageError(String);
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Days {
+    All,
+    One(u32),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RunArgs {
+    pub year: u32,
+    pub days: Days,
+    pub part: Option<Part>,
+    pub input: Option<String>,
+    pub example: bool,
+    pub format: Format,
+    pub timeout: Duration,
+    // None for one thread per CPU.
+    pub threads: Option<usize>,
+    pub explain: bool,
+}
+
+impl RunArgs {
+    pub fn parts(&self) -> Vec<Part> {
+        match self.part {
+            Some(part) => vec![part],
+            None => Part::BOTH.to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct VerifyArgs {
+    pub days: Days,
+    pub answers: Option<String>,
+    pub record: bool,
+    pub timeout: Duration,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BenchArgs {
+    pub run: RunArgs,
+    pub warmup: usize,
+    pub iters: usize,
+    pub record: bool,
+    pub history: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CompareArgs {
+    pub threshold: f64,
+    pub baseline: Option<String>,
+    pub history: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct WatchArgs {
+    pub day: u32,
+    pub example: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct NewDayArgs {
+    pub day: u32,
+    pub title: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FetchArgs {
+    pub day: u32,
+    pub base_url: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SubmitArgs {
+    pub day: u32,
+    pub part: Part,
+    pub base_url: Option<String>,
+    pub answers: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GenArgs {
+    pub day: u32,
+    // None to pick one from the clock.
+    pub seed: Option<u64>,
+    pub size: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DiffTestArgs {
+    pub day: u32,
+    pub seed: u64,
+    pub count: usize,
+    pub max_size: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct LintArgs {
+    pub day: u32,
+    pub input: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ReplArgs {
+    pub day: u32,
+    pub input: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Cli {
+    pub year: u32,
+    pub command: Command,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    List,
+    Run(RunArgs),
+    Verify(VerifyArgs),
+    Bench(BenchArgs),
+    Compare(CompareArgs),
+    Watch(WatchArgs),
+    NewDay(NewDayArgs),
+    Fetch(FetchArgs),
+    Submit(SubmitArgs),
+    Gen(GenArgs),
+    DiffTest(DiffTestArgs),
+    Lint(LintArgs),
+    Repl(ReplArgs),
+}
+
+fn usage_err<T>(msg: impl Into<String>) -> Result<T, UsageError> {
+    Err(UsageError(msg.into()))
+}
+
+// Value of an option that takes an argument, e.g. the 7 in --day 7.
+fn value<I>(flag: &str, args: &mut I) -> Result<String, UsageError>
+where
+    I: Iterator<Item = String>,
+{
+    match args.next() {
+        Some(v) if !v.starts_with("--") => Ok(v),
+        _ => usage_err(format!("{} expects a value", flag)),
+    }
+}
+
+fn parse_day(s: &str) -> Result<u32, UsageError> {
+    match s.parse::<u32>() {
+        Ok(day) if (1..=25).contains(&day) => Ok(day),
+        _ => usage_err(format!("invalid day '{}', expected 1 to 25", s)),
+    }
+}
+
+fn parse_part(s: &str) -> Result<Part, UsageError> {
+    match s {
+        "1" => Ok(Part::One),
+        "2" => Ok(Part::Two),
+        _ => usage_err(format!("invalid part '{}', expected 1 or 2", s)),
+    }
+}
+
+fn parse_format(s: &str) -> Result<Format, UsageError> {
+    match s {
+        "text" => Ok(Format::Text),
+        "json" => Ok(Format::Json),
+        "csv" => Ok(Format::Csv),
+        _ => usage_err(format!(
+            "invalid format '{}', expected text, json or csv",
+            s
+        )),
+    }
+}
+
+fn parse_timeout(s: &str) -> Result<Duration, UsageError> {
+    match s.parse::<f64>() {
+        Ok(secs) if secs > 0.0 && secs.is_finite() => Ok(Duration::from_secs_f64(secs)),
+        _ => usage_err(format!("invalid timeout '{}', expected seconds", s)),
+    }
+}
+
+fn parse_year(s: &str) -> Result<u32, UsageError> {
+    match s.parse::<u32>() {
+        Ok(year) if year >= 2015 => Ok(year),
+        _ => usage_err(format!("invalid year '{}', expected 2015 or later", s)),
+    }
+}
+
+fn parse_run<I>(year: u32, mut args: I) -> Result<RunArgs, UsageError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut all = false;
+    let mut day = None;
+    let mut part = None;
+    let mut input = None;
+    let mut example = false;
+    let mut format = config::get().format.unwrap_or(Format::Text);
+    let mut timeout = config::get().timeout.unwrap_or(DEFAULT_TIMEOUT);
+    let mut threads = None;
+    let mut explain = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--all" => all = true,
+            "--day" => day = Some(parse_day(&value(&arg, &mut args)?)?),
+            "--part" => part = Some(parse_part(&value(&arg, &mut args)?)?),
+            "--input" => input = Some(value(&arg, &mut args)?),
+            "--example" => example = true,
+            "--format" => format = parse_format(&value(&arg, &mut args)?)?,
+            "--timeout" => timeout = parse_timeout(&value(&arg, &mut args)?)?,
+            "--jobs" => threads = Some(parse_count(&arg, &value(&arg, &mut args)?, 1)?),
+            "--explain" => explain = true,
+            _ => return usage_err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    let days = match (all, day) {
+        (true, None) => Days::All,
+        (false, Some(day)) => Days::One(day),
+        (true, Some(_)) => return usage_err("--all and --day are mutually exclusive"),
+        (false, None) => return usage_err("run expects --day <n> or --all"),
+    };
+    if input.is_some() && (example || days == Days::All) {
+        return usage_err("--input needs a single --day and no --example");
+    }
+    if explain && format != Format::Text {
+        return usage_err("--explain only goes with --format text");
+    }
+
+    Ok(RunArgs {
+        year,
+        days,
+        part,
+        input,
+        example,
+        format,
+        timeout,
+        threads,
+        explain,
+    })
+}
+
+fn parse_verify<I>(mut args: I) -> Result<VerifyArgs, UsageError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut days = Days::All;
+    let mut answers = None;
+    let mut record = false;
+    let mut timeout = config::get().timeout.unwrap_or(DEFAULT_TIMEOUT);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--day" => days = Days::One(parse_day(&value(&arg, &mut args)?)?),
+            "--answers" => answers = Some(value(&arg, &mut args)?),
+            "--record" => record = true,
+            "--timeout" => timeout = parse_timeout(&value(&arg, &mut args)?)?,
+            _ => return usage_err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+    Ok(VerifyArgs {
+        days,
+        answers,
+        record,
+        timeout,
+    })
+}
+
+fn parse_count(flag: &str, s: &str, min: usize) -> Result<usize, UsageError> {
+    match s.parse::<usize>() {
+        Ok(n) if n >= min => Ok(n),
+        _ => usage_err(format!("{} expects a number of at least {}", flag, min)),
+    }
+}
+
+fn parse_bench<I>(year: u32, mut args: I) -> Result<BenchArgs, UsageError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut warmup = config::get().warmup.unwrap_or(5);
+    let mut iters = config::get().iters.unwrap_or(100);
+    let mut record = false;
+    let mut history = None;
+    let mut run_args = vec![];
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--warmup" => warmup = parse_count(&arg, &value(&arg, &mut args)?, 0)?,
+            "--iters" => iters = parse_count(&arg, &value(&arg, &mut args)?, 1)?,
+            "--record" => record = true,
+            "--history" => history = Some(value(&arg, &mut args)?),
+            "--format" => return usage_err("bench only prints a table"),
+            "--jobs" => return usage_err("bench runs one stage at a time"),
+            "--explain" => return usage_err("bench does not trace"),
+            _ => run_args.push(arg),
+        }
+    }
+    if !run_args.iter().any(|a| a == "--day" || a == "--all") {
+        run_args.push("--all".to_string());
+    }
+
+    Ok(BenchArgs {
+        run: parse_run(year, run_args.into_iter())?,
+        warmup,
+        iters,
+        record,
+        history,
+    })
+}
+
+fn parse_compare<I>(mut args: I) -> Result<CompareArgs, UsageError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut threshold = 10.0;
+    let mut baseline = None;
+    let mut history = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--threshold" => {
+                let v = value(&arg, &mut args)?;
+                threshold = match v.trim_end_matches('%').parse::<f64>() {
+                    Ok(t) if t >= 0.0 => t,
+                    _ => return usage_err(format!("invalid threshold '{}'", v)),
+                };
+            }
+            "--baseline" => baseline = Some(value(&arg, &mut args)?),
+            "--history" => history = Some(value(&arg, &mut args)?),
+            _ => return usage_err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+    Ok(CompareArgs {
+        threshold: threshold / 100.0,
+        baseline,
+        history,
+    })
+}
+
+fn parse_watch<I>(mut args: I) -> Result<WatchArgs, UsageError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut day = None;
+    let mut example = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--day" => day = Some(parse_day(&value(&arg, &mut args)?)?),
+            "--example" => example = true,
+            _ => return usage_err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+    match day {
+        Some(day) => Ok(WatchArgs { day, example }),
+        None => usage_err("watch expects --day <n>"),
+    }
+}
+
+fn parse_new_day<I>(mut args: I) -> Result<NewDayArgs, UsageError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut day = None;
+    let mut title = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--title" => title = Some(value(&arg, &mut args)?),
+            _ if day.is_none() && !arg.starts_with("--") => day = Some(parse_day(&arg)?),
+            _ => return usage_err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+    let day = match day {
+        Some(day) => day,
+        None => return usage_err("new-day expects a day"),
+    };
+    Ok(NewDayArgs {
+        day,
+        title: title.unwrap_or_else(|| format!("Day {}", day)),
+    })
+}
+
+fn parse_fetch<I>(mut args: I) -> Result<FetchArgs, UsageError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut day = None;
+    let mut base_url = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--base-url" => base_url = Some(value(&arg, &mut args)?),
+            _ if day.is_none() && !arg.starts_with("--") => day = Some(parse_day(&arg)?),
+            _ => return usage_err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+    match day {
+        Some(day) => Ok(FetchArgs { day, base_url }),
+        None => usage_err("fetch expects a day"),
+    }
+}
+
+fn parse_submit<I>(mut args: I) -> Result<SubmitArgs, UsageError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut day = None;
+    let mut part = None;
+    let mut base_url = None;
+    let mut answers = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--base-url" => base_url = Some(value(&arg, &mut args)?),
+            "--answers" => answers = Some(value(&arg, &mut args)?),
+            _ if arg.starts_with("--") => {
+                return usage_err(format!("unexpected argument '{}'", arg))
+            }
+            _ if day.is_none() => day = Some(parse_day(&arg)?),
+            _ if part.is_none() => part = Some(parse_part(&arg)?),
+            _ => return usage_err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+    match (day, part) {
+        (Some(day), Some(part)) => Ok(SubmitArgs {
+            day,
+            part,
+            base_url,
+            answers,
+        }),
+        _ => usage_err("submit expects a day and a part"),
+    }
+}
+
+fn parse_seed(s: &str) -> Result<u64, UsageError> {
+    s.parse()
+        .or_else(|_| usage_err(format!("invalid seed '{}'", s)))
+}
+
+fn parse_gen<I>(mut args: I) -> Result<GenArgs, UsageError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut day = None;
+    let mut seed = None;
+    let mut size = 100;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = Some(parse_seed(&value(&arg, &mut args)?)?),
+            "--size" => size = parse_count(&arg, &value(&arg, &mut args)?, 1)?,
+            _ if day.is_none() && !arg.starts_with("--") => day = Some(parse_day(&arg)?),
+            _ => return usage_err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+    match day {
+        Some(day) => Ok(GenArgs { day, seed, size }),
+        None => usage_err("gen expects a day"),
+    }
+}
+
+fn parse_diff_test<I>(mut args: I) -> Result<DiffTestArgs, UsageError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut day = None;
+    let mut seed = 0;
+    let mut count = 1000;
+    let mut max_size = 20;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = parse_seed(&value(&arg, &mut args)?)?,
+            "--count" => count = parse_count(&arg, &value(&arg, &mut args)?, 1)?,
+            "--size" => max_size = parse_count(&arg, &value(&arg, &mut args)?, 1)?,
+            _ if day.is_none() && !arg.starts_with("--") => day = Some(parse_day(&arg)?),
+            _ => return usage_err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+    match day {
+        Some(day) => Ok(DiffTestArgs {
+            day,
+            seed,
+            count,
+            max_size,
+        }),
+        None => usage_err("difftest expects a day"),
+    }
+}
+
+fn parse_lint<I>(args: I) -> Result<LintArgs, UsageError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut day = None;
+    let mut input = None;
+
+    for arg in args {
+        match arg.as_str() {
+            _ if arg.starts_with("--") => {
+                return usage_err(format!("unexpected argument '{}'", arg))
+            }
+            _ if day.is_none() => day = Some(parse_day(&arg)?),
+            _ if input.is_none() => input = Some(arg),
+            _ => return usage_err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+    match day {
+        Some(day) => Ok(LintArgs { day, input }),
+        None => usage_err("lint expects a day"),
+    }
+}
+
+fn parse_repl<I>(args: I) -> Result<ReplArgs, UsageError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut day = None;
+    let mut input = None;
+
+    for arg in args {
+        match arg.as_str() {
+            "-" => return usage_err("repl reads its queries from stdin, not the input"),
+            _ if arg.starts_with("--") => {
+                return usage_err(format!("unexpected argument '{}'", arg))
+            }
+            _ if day.is_none() => day = Some(parse_day(&arg)?),
+            _ if input.is_none() => input = Some(arg),
+            _ => return usage_err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+    match day {
+        Some(day) => Ok(ReplArgs { day, input }),
+        None => usage_err("repl expects a day"),
+    }
+}
+
+// Parse the arguments following the program name. --year applies to
+// every command, so it is taken out wherever it is.
+pub fn parse<I>(mut args: I) -> Result<Cli, UsageError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut year = config::get().year.unwrap_or(registry::DEFAULT_YEAR);
+    let mut rest = vec![];
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--year" => year = parse_year(&value(&arg, &mut args)?)?,
+            _ => rest.push(arg),
+        }
+    }
+    let mut args = rest.into_iter();
+
+    let command = match args.next().as_deref() {
+        Some("list") => Ok(Command::List),
+        Some("run") => parse_run(year, args).map(Command::Run),
+        Some("verify") => parse_verify(args).map(Command::Verify),
+        Some("bench") => parse_bench(year, args).map(Command::Bench),
+        Some("compare") => parse_compare(args).map(Command::Compare),
+        Some("watch") => parse_watch(args).map(Command::Watch),
+        Some("new-day") => parse_new_day(args).map(Command::NewDay),
+        Some("fetch") => parse_fetch(args).map(Command::Fetch),
+        Some("submit") => parse_submit(args).map(Command::Submit),
+        Some("gen") => parse_gen(args).map(Command::Gen),
+        Some("difftest") => parse_diff_test(args).map(Command::DiffTest),
+        Some("lint") => parse_lint(args).map(Command::Lint),
+        Some("repl") => parse_repl(args).map(Command::Repl),
+        Some(cmd) => usage_err(format!("unknown command '{}'", cmd)),
+        None => usage_err("missing command"),
+    }?;
+    Ok(Cli { year, command })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse, BenchArgs, Cli, Command, Days, DiffTestArgs, FetchArgs, GenArgs, LintArgs,
+        NewDayArgs, ReplArgs, RunArgs, SubmitArgs, UsageError, WatchArgs, DEFAULT_TIMEOUT,
+    };
+    use crate::format::Format;
+    use aoc23::solution::Part;
+    use std::time::Duration;
+
+    fn parse_str(args: &str) -> Result<Command, UsageError> {
+        parse(args.split_whitespace().map(String::from)).map(|cli| cli.command)
+    }
+
+    #[test]
+    fn test_run_flags() {
+        assert_eq!(
+            Ok(Command::Run(RunArgs {
+                year: 2023,
+                days: Days::One(7),
+                part: Some(Part::One),
+                input: Some("in.txt".to_string()),
+                example: false,
+                format: Format::Text,
+                timeout: Duration::from_millis(500),
+                threads: None,
+                explain: false,
+            })),
+            parse_str("run --day 7 --part 1 --input in.txt --timeout 0.5")
+        );
+        assert_eq!(
+            Ok(Command::Run(RunArgs {
+                year: 2023,
+                days: Days::All,
+                part: None,
+                input: None,
+                example: true,
+                format: Format::Csv,
+                timeout: DEFAULT_TIMEOUT,
+                threads: Some(3),
+                explain: false,
+            })),
+            parse_str("run --example --all --format csv --jobs 3")
+        );
+    }
+
+    #[test]
+    fn test_year() {
+        let cli = parse("gen --year 2022 3".split_whitespace().map(String::from)).unwrap();
+        assert_eq!(
+            Cli {
+                year: 2022,
+                command: Command::Gen(GenArgs {
+                    day: 3,
+                    seed: None,
+                    size: 100,
+                }),
+            },
+            cli
+        );
+        match parse_str("--year 2015 run --day 1") {
+            Ok(Command::Run(args)) => assert_eq!(2015, args.year),
+            other => panic!("{:?}", other),
+        }
+        assert!(parse_str("--year 2014 list").is_err());
+        assert!(parse_str("list --year").is_err());
+    }
+
+    #[test]
+    fn test_bench_flags() {
+        assert_eq!(
+            Ok(Command::Bench(BenchArgs {
+                run: RunArgs {
+                    year: 2023,
+                    days: Days::All,
+                    part: Some(Part::Two),
+                    input: None,
+                    example: false,
+                    format: Format::Text,
+                    timeout: DEFAULT_TIMEOUT,
+                    threads: None,
+                    explain: false,
+                },
+                warmup: 0,
+                iters: 10,
+                record: false,
+                history: None,
+            })),
+            parse_str("bench --iters 10 --part 2 --warmup 0")
+        );
+        assert!(parse_str("bench --iters 0").is_err());
+    }
+
+    #[test]
+    fn test_watch_args() {
+        assert_eq!(
+            Ok(Command::Watch(WatchArgs {
+                day: 7,
+                example: true,
+            })),
+            parse_str("watch --example --day 7")
+        );
+        assert!(parse_str("watch").is_err());
+        assert!(parse_str("watch --all").is_err());
+    }
+
+    #[test]
+    fn test_new_day_args() {
+        assert_eq!(
+            Ok(Command::NewDay(NewDayArgs {
+                day: 9,
+                title: "Day 9".to_string(),
+            })),
+            parse_str("new-day 9")
+        );
+        assert_eq!(
+            Ok(Command::NewDay(NewDayArgs {
+                day: 10,
+                title: "Pipe Maze".to_string(),
+            })),
+            parse(
+                ["new-day", "--title", "Pipe Maze", "10"]
+                    .map(String::from)
+                    .into_iter()
+            )
+            .map(|cli| cli.command)
+        );
+        assert!(parse_str("new-day").is_err());
+        assert!(parse_str("new-day 9 10").is_err());
+        assert!(parse_str("new-day 26").is_err());
+    }
+
+    #[test]
+    fn test_fetch_args() {
+        assert_eq!(
+            Ok(Command::Fetch(FetchArgs {
+                day: 3,
+                base_url: Some("http://localhost:8080".to_string()),
+            })),
+            parse_str("fetch 3 --base-url http://localhost:8080")
+        );
+        assert!(parse_str("fetch").is_err());
+        assert!(parse_str("fetch --base-url").is_err());
+    }
+
+    #[test]
+    fn test_submit_args() {
+        assert_eq!(
+            Ok(Command::Submit(SubmitArgs {
+                day: 5,
+                part: Part::Two,
+                base_url: None,
+                answers: None,
+            })),
+            parse_str("submit 5 2")
+        );
+        assert!(parse_str("submit 5").is_err());
+        assert!(parse_str("submit 5 3").is_err());
+        assert!(parse_str("submit 5 2 1").is_err());
+    }
+
+    #[test]
+    fn test_gen_args() {
+        assert_eq!(
+            Ok(Command::Gen(GenArgs {
+                day: 8,
+                seed: Some(42),
+                size: 100,
+            })),
+            parse_str("gen 8 --seed 42")
+        );
+        assert_eq!(
+            Ok(Command::Gen(GenArgs {
+                day: 5,
+                seed: None,
+                size: 3,
+            })),
+            parse_str("gen --size 3 5")
+        );
+        assert!(parse_str("gen").is_err());
+        assert!(parse_str("gen 5 --size 0").is_err());
+        assert!(parse_str("gen 5 --seed -1").is_err());
+    }
+
+    #[test]
+    fn test_diff_test_args() {
+        assert_eq!(
+            Ok(Command::DiffTest(DiffTestArgs {
+                day: 5,
+                seed: 0,
+                count: 1000,
+                max_size: 20,
+            })),
+            parse_str("difftest 5")
+        );
+        assert_eq!(
+            Ok(Command::DiffTest(DiffTestArgs {
+                day: 6,
+                seed: 7,
+                count: 50,
+                max_size: 3,
+            })),
+            parse_str("difftest 6 --seed 7 --count 50 --size 3")
+        );
+        assert!(parse_str("difftest").is_err());
+        assert!(parse_str("difftest 5 --count 0").is_err());
+    }
+
+    #[test]
+    fn test_lint_args() {
+        assert_eq!(
+            Ok(Command::Lint(LintArgs {
+                day: 3,
+                input: Some("-".to_string()),
+            })),
+            parse_str("lint 3 -")
+        );
+        assert_eq!(
+            Ok(Command::Lint(LintArgs {
+                day: 3,
+                input: None,
+            })),
+            parse_str("lint 3")
+        );
+        assert!(parse_str("lint").is_err());
+        assert!(parse_str("lint 3 a b").is_err());
+        assert!(parse_str("lint 3 --example").is_err());
+    }
+
+    #[test]
+    fn test_repl_args() {
+        assert_eq!(
+            Ok(Command::Repl(ReplArgs {
+                day: 7,
+                input: Some("in.txt".to_string()),
+            })),
+            parse_str("repl 7 in.txt")
+        );
+        assert!(parse_str("repl").is_err());
+        assert!(parse_str("repl 7 -").is_err());
+    }
+
+    #[test]
+    fn test_usage_errors() {
+        assert!(parse_str("").is_err());
+        assert!(parse_str("solve").is_err());
+        assert!(parse_str("run").is_err());
+        assert!(parse_str("run --day").is_err());
+        assert!(parse_str("run --day 26").is_err());
+        assert!(parse_str("run --day 7 --part 3").is_err());
+        assert!(parse_str("run --day 7 --all").is_err());
+        assert!(parse_str("run --all --input in.txt").is_err());
+        assert!(parse_str("run --day 7 --verbose").is_err());
+        assert!(parse_str("run --day 7 --format xml").is_err());
+        assert!(parse_str("run --day 7 --timeout 0").is_err());
+        assert!(parse_str("bench --format json").is_err());
+        assert!(parse_str("run --all --jobs 0").is_err());
+        assert!(parse_str("run --all --explain --format json").is_err());
+        assert!(parse_str("bench --explain").is_err());
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+use std::{env, fs, io};
+
+use aoc23::params::Params;
+use aoc23::registry;
+use aoc23::toml::{Document, Value};
+
+use crate::format::Format;
+
+// Defaults read from aoc.toml at startup, for settings that would
+// otherwise need a flag or an environment variable on every run:
+//
+//     year = 2023
+//     data_dir = "inputs"
+//     format = "json"
+//     timeout = 30
+//
+//     [bench]
+//     warmup = 2
+//     iters = 20
+//
+//     [fetch]
+//     base_url = "http://localhost:8080"
+//     session_file = "~/.config/aoc/session"
+//
+//     [day2]
+//     red = 12
+//
+//     [2024.day3]
+//     width = 50
+//
+// Flags and environment variables win over the file, and the file over
+// the built-in defaults. Every setting is optional, as is the file.
+
+pub const DEFAULT_PATH: &str = "aoc.toml";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Config {
+    pub year: Option<u32>,
+    pub data_dir: Option<PathBuf>,
+    pub format: Option<Format>,
+    pub timeout: Option<Duration>,
+    pub warmup: Option<usize>,
+    pub iters: Option<usize>,
+    pub base_url: Option<String>,
+    pub session_file: Option<PathBuf>,
+    // Puzzle parameters by year, day and name, see aoc23::params. The
+    // year is None for [dayN] tables, which apply to the year being run.
+    pub params: BTreeMap<(Option<u32>, u32, String), i64>,
+}
+
+impl Config {
+    // The parameters of a day of `year`, the year being run, which the
+    // flags may have picked. [YYYY.dayN] settings sort after the [dayN]
+    // ones and so win over them.
+    pub fn params(&self, year: u32, day: u32) -> Params {
+        let mut params = Params::new();
+        for ((y, d, name), value) in &self.params {
+            if *d == day && y.is_none_or(|y| y == year) {
+                params.set(name, *value);
+            }
+        }
+        params
+    }
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+static DEFAULT: Config = Config {
+    year: None,
+    data_dir: None,
+    format: None,
+    timeout: None,
+    warmup: None,
+    iters: None,
+    base_url: None,
+    session_file: None,
+    params: BTreeMap::new(),
+};
+
+// The config loaded at startup, or the empty one before that, as in
+// tests.
+pub fn get() -> &'static Config {
+    CONFIG.get().unwrap_or(&DEFAULT)
+}
+
+// The config file: AOC_CONFIG if set, else aoc.toml if there is one.
+pub fn path() -> Option<PathBuf> {
+    match env::var_os("AOC_CONFIG") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => Some(PathBuf::from(DEFAULT_PATH)).filter(|p| p.exists()),
+    }
+}
+
+// Load the config file once, at startup.
+pub fn init() -> io::Result<()> {
+    let config = match path() {
+        Some(path) => load(&path)?,
+        None => Config::default(),
+    };
+    let _ = CONFIG.set(config);
+    Ok(())
+}
+
+pub fn load(path: &Path) -> io::Result<Config> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+    parse(&text).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), e),
+        )
+    })
+}
+
+fn expect_str(table: &str, key: &str, value: &Value) -> Result<String, String> {
+    match value {
+        Value::Str(s) => Ok(s.clone()),
+        _ => Err(format!("{} expects a string", name(table, key))),
+    }
+}
+
+fn expect_count(table: &str, key: &str, value: &Value, min: i64) -> Result<usize, String> {
+    match value {
+        Value::Int(n) if *n >= min => Ok(*n as usize),
+        _ => Err(format!(
+            "{} expects a number of at least {}",
+            name(table, key),
+            min
+        )),
+    }
+}
+
+fn name(table: &str, key: &str) -> String {
+    match table {
+        "" => key.to_string(),
+        _ => format!("{}.{}", table, key),
+    }
+}
+
+// A leading ~/ stands for the home directory.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+pub fn parse(text: &str) -> Result<Config, String> {
+    let doc = Document::parse(text).map_err(|e| e.to_string())?;
+    let mut config = Config::default();
+
+    for (table, entries) in &doc.tables {
+        for (key, value) in entries {
+            match (table.as_str(), key.as_str()) {
+                ("", "year") => {
+                    config.year = match value {
+                        Value::Int(n) if *n >= 2015 => Some(*n as u32),
+                        _ => return Err("year expects a year from 2015 on".to_string()),
+                    }
+                }
+                ("", "data_dir") => {
+                    config.data_dir = Some(expand_home(&expect_str(table, key, value)?))
+                }
+                ("", "format") => {
+                    config.format = Some(match expect_str(table, key, value)?.as_str() {
+                        "text" => Format::Text,
+                        "json" => Format::Json,
+                        "csv" => Format::Csv,
+                        other => return Err(format!("invalid format '{}'", other)),
+                    })
+                }
+                ("", "timeout") => {
+                    let secs = match value {
+                        Value::Int(n) => *n as f64,
+                        Value::Float(n) => *n,
+                        _ => f64::NAN,
+                    };
+                    if !(secs > 0.0 && secs.is_finite()) {
+                        return Err("timeout expects seconds".to_string());
+                    }
+                    config.timeout = Some(Duration::from_secs_f64(secs));
+                }
+                ("bench", "warmup") => config.warmup = Some(expect_count(table, key, value, 0)?),
+                ("bench", "iters") => config.iters = Some(expect_count(table, key, value, 1)?),
+                ("fetch", "base_url") => config.base_url = Some(expect_str(table, key, value)?),
+                ("fetch", "session_file") => {
+                    config.session_file = Some(expand_home(&expect_str(table, key, value)?))
+                }
+                (day, param) if day.starts_with("day") || day.contains(".day") => {
+                    // [YYYY.dayN] is a day of that year, [dayN] one of the
+                    // year being run, which the flags may still change, so
+                    // it only has to be a parameter of day N of some year.
+                    let no_such_day = || format!("[{}]: no such day", table);
+                    let (year, day) = match day.split_once('.') {
+                        Some((year, day)) => (Some(year.parse().map_err(|_| no_such_day())?), day),
+                        None => (None, day),
+                    };
+                    let day: u32 = day[3..].parse().map_err(|_| no_such_day())?;
+                    let solvers: Vec<_> = registry::all()
+                        .iter()
+                        .map(|d| d.solver)
+                        .filter(|s| s.day() == day && year.is_none_or(|y| s.year() == y))
+                        .collect();
+                    if solvers.is_empty() {
+                        return Err(no_such_day());
+                    }
+                    if !solvers.iter().any(|s| s.params().contains(&param)) {
+                        return Err(format!("{} is not a parameter of {}", param, table));
+                    }
+                    let Value::Int(n) = value else {
+                        return Err(format!("{} expects a number", name(table, key)));
+                    };
+                    config.params.insert((year, day, param.to_string()), *n);
+                }
+                _ => return Err(format!("unknown setting {}", name(table, key))),
+            }
+        }
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Config};
+    use crate::format::Format;
+    use aoc23::params::Params;
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse() {
+        let config = parse(
+            r#"
+data_dir = "inputs"
+format = "csv"
+timeout = 2.5
+
+[bench]
+iters = 20
+
+[fetch]
+base_url = "http://localhost:8080"
+
+[day2]
+red = 20
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            Config {
+                year: None,
+                data_dir: Some(PathBuf::from("inputs")),
+                format: Some(Format::Csv),
+                timeout: Some(Duration::from_millis(2500)),
+                warmup: None,
+                iters: Some(20),
+                base_url: Some("http://localhost:8080".to_string()),
+                session_file: None,
+                params: [((None, 2, "red".to_string()), 20)].into(),
+            },
+            config
+        );
+        assert_eq!(Config::default(), parse("").unwrap());
+        assert_eq!(
+            [((Some(2023), 2, "green".to_string()), 1)]
+                .into_iter()
+                .collect::<BTreeMap<_, _>>(),
+            parse("year = 2015\n[2023.day2]\ngreen = 1").unwrap().params
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(parse("format = \"xml\"").is_err());
+        assert!(parse("timeout = 0").is_err());
+        assert!(parse("timeout = \"soon\"").is_err());
+        assert!(parse("[bench]\niters = 0").is_err());
+        assert!(parse("data_dirr = \"x\"").is_err());
+        assert!(parse("[day2]\npurple = 3").is_err());
+        assert!(parse("[day2]\nred = \"12\"").is_err());
+        assert!(parse("[day99]\nred = 1").is_err());
+        assert!(parse("[2022.day2]\nred = 1").is_err());
+        assert!(parse("year = 2014").is_err());
+    }
+
+    #[test]
+    fn test_params() {
+        // [day2] follows the year being run, not the one in the file.
+        let config =
+            parse("year = 2022\n[day2]\nred = 1\ngreen = 2\n[2023.day2]\ngreen = 3").unwrap();
+        assert_eq!(
+            Params::new().with("red", 1).with("green", 3),
+            config.params(2023, 2)
+        );
+        assert_eq!(Params::new(), config.params(2023, 3));
+    }
+}
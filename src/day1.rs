@@ -1,15 +1,153 @@
-pub fn part_one(input: &str) -> u32 {
-    let mut sum = 0;
-    for line in input.lines() {
-        let first = line.chars().find_map(|c| c.to_digit(10)).unwrap();
-        let last = line.chars().rev().find_map(|c| c.to_digit(10)).unwrap();
-        sum += (first * 10) + last;
+use crate::error::PuzzleError;
+use crate::parse::{self, Line};
+use crate::rng::Rng;
+use crate::solution::{Explain, Generator, Lint, Part, Solution, Step};
+
+pub struct Calibration {
+    no: usize,
+    text: String,
+}
+
+impl Calibration {
+    pub fn line_no(&self) -> usize {
+        self.no
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    fn line(&self) -> Line<'_> {
+        Line {
+            no: self.no,
+            text: &self.text,
+        }
+    }
+
+    fn value<F>(&self, digit_at: F) -> Result<u32, PuzzleError>
+    where
+        F: Fn(&str) -> Option<u32>,
+    {
+        let line = &self.text;
+        let first = line.char_indices().find_map(|(i, _)| digit_at(&line[i..]));
+        let last = line
+            .char_indices()
+            .rev()
+            .find_map(|(i, _)| digit_at(&line[i..]));
+        match (first, last) {
+            (Some(first), Some(last)) => Ok((first * 10) + last),
+            _ => Err(self.line().error(line, "no digit on line")),
+        }
     }
-    sum
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    const YEAR: u32 = 2023;
+    const DAY: u32 = 1;
+    const TITLE: &'static str = "Trebuchet?!";
+
+    type Model = Vec<Calibration>;
+    type PartOne = u32;
+    type PartTwo = u32;
+
+    fn parse(input: &str) -> Result<Self::Model, PuzzleError> {
+        Ok(parse(input))
+    }
+
+    fn part_one(lines: &Self::Model) -> Result<u32, PuzzleError> {
+        part_one(lines)
+    }
+
+    fn part_two(lines: &Self::Model) -> Result<u32, PuzzleError> {
+        part_two(lines)
+    }
+}
+
+impl Lint for Day1 {
+    // Every line without a digit, real or spelled out. A line with only
+    // spelled out digits is fine, as part two's examples are like that.
+    fn lint(input: &str) -> Vec<PuzzleError> {
+        parse(input)
+            .iter()
+            .filter_map(|line| line.value(string_to_digit).err())
+            .collect()
+    }
+}
+
+impl Explain for Day1 {
+    // Every digit found on each line, with its column, and the value
+    // made of the first and the last.
+    fn explain(lines: &Self::Model, part: Part) -> Vec<Step> {
+        let digit_at = match part {
+            Part::One => char_to_digit,
+            Part::Two => string_to_digit,
+        };
+        lines
+            .iter()
+            .map(|line| {
+                let text = &line.text;
+                let digits: Vec<String> = text
+                    .char_indices()
+                    .enumerate()
+                    .filter_map(|(col, (i, _))| {
+                        digit_at(&text[i..]).map(|d| format!("{}@{}", d, col + 1))
+                    })
+                    .collect();
+                let step = Step::new()
+                    .with("line", line.no)
+                    .with("digits", digits.join(" "));
+                match line.value(digit_at) {
+                    Ok(value) => step.with("value", value),
+                    Err(e) => step.with("error", e.message),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Generator for Day1 {
+    // Lines of letters, digits and spelled out digits, each with at
+    // least one real digit so that both parts apply.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        const WORDS: [&str; 9] = [
+            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+        ];
+        let mut input = String::new();
+        for _ in 0..size {
+            let pieces = rng.index(6) + 1;
+            let digit = rng.index(pieces);
+            for i in 0..pieces {
+                match rng.index(3) {
+                    _ if i == digit => input.push(char::from(b'1' + rng.index(9) as u8)),
+                    0 => input.push_str(rng.pick::<&str>(&WORDS)),
+                    1 => input.push(char::from(b'1' + rng.index(9) as u8)),
+                    _ => (0..rng.index(4) + 1)
+                        .for_each(|_| input.push(char::from(b'a' + rng.index(26) as u8))),
+                }
+            }
+            input.push('\n');
+        }
+        input
+    }
+}
+
+pub fn parse(input: &str) -> Vec<Calibration> {
+    parse::lines(input)
+        .map(|l| Calibration {
+            no: l.no,
+            text: l.text.to_string(),
+        })
+        .collect()
+}
+
+fn char_to_digit(input: &str) -> Option<u32> {
+    input.chars().next().and_then(|c| c.to_digit(10))
 }
 
 fn string_to_digit(input: &str) -> Option<u32> {
-    if let Some(Some(dig)) = input.chars().next().map(|c| c.to_digit(10)) {
+    if let Some(dig) = char_to_digit(input) {
         return Some(dig);
     }
 
@@ -31,17 +169,46 @@ fn string_to_digit(input: &str) -> Option<u32> {
     None
 }
 
-pub fn part_two(input: &str) -> u32 {
+pub fn part_one(lines: &[Calibration]) -> Result<u32, PuzzleError> {
     let mut sum = 0;
-    for line in input.lines() {
-        let first = (0..line.len())
-            .find_map(|i| string_to_digit(&line[i..]))
-            .unwrap();
-        let last = (0..line.len())
-            .rev()
-            .find_map(|i| string_to_digit(&line[i..]))
-            .unwrap();
-        sum += (first * 10) + last;
+    for line in lines {
+        sum += line.value(char_to_digit)?;
+    }
+    Ok(sum)
+}
+
+pub fn part_two(lines: &[Calibration]) -> Result<u32, PuzzleError> {
+    let mut sum = 0;
+    for line in lines {
+        sum += line.value(string_to_digit)?;
+    }
+    Ok(sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, part_one, part_two, Day1};
+    use crate::solution::{Explain, Lint, Part};
+
+    #[test]
+    fn test_non_ascii() {
+        let lines = parse("é1xtwo\n");
+        assert_eq!(Ok(11), part_one(&lines));
+        assert_eq!(Ok(12), part_two(&lines));
+        let err = part_one(&parse("1\nñ\n")).unwrap_err();
+        assert_eq!(2, err.span.unwrap().line);
+
+        let steps = Day1::explain(&lines, Part::Two);
+        assert_eq!("line=1 digits=\"1@2 2@4\" value=12", steps[0].to_string());
+    }
+
+    #[test]
+    fn test_lint_no_digit() {
+        let errors = Day1::lint("abc\ntwo\nxyz\n");
+        let lines: Vec<_> = errors
+            .iter()
+            .map(|e| e.span.as_ref().unwrap().line)
+            .collect();
+        assert_eq!(vec![1, 3], lines);
     }
-    sum
 }
@@ -1,19 +1,25 @@
 use std::cmp;
 use std::str::FromStr;
 
-#[derive(Debug, Default)]
-struct Draw {
-    red: isize,
-    green: isize,
-    blue: isize,
+use crate::error::PuzzleError;
+use crate::params::Params;
+use crate::parse::{self, Line};
+use crate::rng::Rng;
+use crate::solution::{Explain, Generator, Lint, Part, Query, Solution, Step};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Draw {
+    pub red: isize,
+    pub green: isize,
+    pub blue: isize,
 }
 
 impl Draw {
-    fn is_valid(&self, max: &Draw) -> bool {
+    pub fn is_valid(&self, max: &Draw) -> bool {
         self.red <= max.red && self.green <= max.green && self.blue <= max.blue
     }
 
-    fn upper_bound(&self, other: &Draw) -> Self {
+    pub fn upper_bound(&self, other: &Draw) -> Self {
         Draw {
             red: cmp::max(self.red, other.red),
             green: cmp::max(self.green, other.green),
@@ -22,86 +28,265 @@ impl Draw {
     }
 }
 
-#[derive(Debug)]
-struct DrawParseErr;
-
 impl FromStr for Draw {
-    type Err = DrawParseErr;
+    type Err = PuzzleError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = Line::detached(s);
         let mut d = Draw {
             red: 0,
             green: 0,
             blue: 0,
         };
-        for (count, color) in s.split(',').map(|col| col.trim().split_once(' ').unwrap()) {
-            let count = count.parse::<isize>().unwrap();
+        for col in s.split(',') {
+            let (count, color) = line.split_once(col.trim(), ' ')?;
+            let count = line.number::<isize>(count)?;
             match color {
                 "red" => d.red = count,
                 "green" => d.green = count,
                 "blue" => d.blue = count,
-                _ => return Err(DrawParseErr),
+                _ => return Err(line.error(color, "unknown color")),
             }
         }
         Ok(d)
     }
 }
 
-struct Game(usize);
-
 #[derive(Debug)]
-struct GameParseErr;
+pub struct Game {
+    id: usize,
+    draws: Vec<Draw>,
+}
+
+impl Game {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn draws(&self) -> &[Draw] {
+        &self.draws
+    }
+
+    // The fewest cubes of each color that make every draw possible.
+    pub fn minimal_bag(&self) -> Draw {
+        self.draws
+            .iter()
+            .fold(Draw::default(), |rm, d| d.upper_bound(&rm))
+    }
+}
 
 impl FromStr for Game {
-    type Err = GameParseErr;
+    type Err = PuzzleError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let id = s
+        let line = Line::detached(s);
+        let (game, draws) = line.split_once(s, ':')?;
+        let id = game
+            .trim()
             .strip_prefix("Game ")
-            .and_then(|s| s.parse::<usize>().ok())
-            .ok_or(GameParseErr)?;
-        Ok(Game(id))
+            .ok_or_else(|| line.error(game, "expected 'Game <id>'"))?;
+        let id = line.number::<usize>(id)?;
+        let draws = draws
+            .split(';')
+            .map(|draw| {
+                let draw = draw.trim();
+                draw.parse::<Draw>().map_err(|e| line.within(draw, e))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Game { id, draws })
     }
 }
 
-pub fn part_one(input: &str) -> usize {
-    let mut sum = 0;
-    let max_draw = Draw {
-        red: 12,
-        green: 13,
-        blue: 14,
-    };
+// The games and the bag part one checks them against.
+pub struct Games {
+    games: Vec<Game>,
+    bag: Draw,
+}
+
+impl Games {
+    pub fn games(&self) -> &[Game] {
+        &self.games
+    }
 
-    for line in input.lines().filter(|l| !l.trim().is_empty()) {
-        let (game, draws) = line.split_once(':').unwrap();
-        let game = game.trim().parse::<Game>().unwrap();
+    pub fn bag(&self) -> &Draw {
+        &self.bag
+    }
+}
 
-        let invalid = draws
-            .split(';')
-            .filter(|draw| {
-                let d = draw.trim().parse::<Draw>().unwrap();
-                !d.is_valid(&max_draw)
+pub struct Day2;
+
+impl Solution for Day2 {
+    const YEAR: u32 = 2023;
+    const DAY: u32 = 2;
+    const TITLE: &'static str = "Cube Conundrum";
+    const PARAMS: &'static [&'static str] = &["red", "green", "blue"];
+
+    type Model = Games;
+    type PartOne = usize;
+    type PartTwo = isize;
+
+    fn parse(input: &str) -> Result<Self::Model, PuzzleError> {
+        Self::parse_with(input, &Params::new())
+    }
+
+    fn parse_with(input: &str, params: &Params) -> Result<Self::Model, PuzzleError> {
+        Ok(Games {
+            games: parse(input)?,
+            bag: Draw {
+                red: params.get("red", BAG.red as i64) as isize,
+                green: params.get("green", BAG.green as i64) as isize,
+                blue: params.get("blue", BAG.blue as i64) as isize,
+            },
+        })
+    }
+
+    fn part_one(games: &Self::Model) -> Result<usize, PuzzleError> {
+        Ok(part_one(&games.games, &games.bag))
+    }
+
+    fn part_two(games: &Self::Model) -> Result<isize, PuzzleError> {
+        Ok(part_two(&games.games))
+    }
+}
+
+impl Lint for Day2 {
+    // Every cube count of every draw is checked on its own, so that all
+    // unknown colors and bad counts show up at once.
+    fn lint(input: &str) -> Vec<PuzzleError> {
+        let mut errors = vec![];
+        for line in parse::lines(input) {
+            let (game, draws) = match line.split_once(line.text, ':') {
+                Ok(parts) => parts,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+            match game.trim().strip_prefix("Game ") {
+                Some(id) => errors.extend(line.number::<usize>(id).err()),
+                None => errors.push(line.error(game, "expected 'Game <id>'")),
+            }
+            for cubes in draws.split([';', ',']) {
+                let cubes = cubes.trim();
+                if let Err(e) = cubes.parse::<Draw>() {
+                    errors.push(line.within(cubes, e));
+                }
+            }
+        }
+        errors
+    }
+}
+
+impl Explain for Day2 {
+    // Part one gives the first draw that makes each game impossible,
+    // part two the fewest cubes of each color and their power.
+    fn explain(games: &Self::Model, part: Part) -> Vec<Step> {
+        let bag = &games.bag;
+        games
+            .games
+            .iter()
+            .map(|game| {
+                let step = Step::new().with("game", game.id);
+                match part {
+                    Part::One => match game.draws.iter().position(|d| !d.is_valid(bag)) {
+                        Some(i) => {
+                            let draw = &game.draws[i];
+                            let over = [
+                                ("red", draw.red, bag.red),
+                                ("green", draw.green, bag.green),
+                                ("blue", draw.blue, bag.blue),
+                            ]
+                            .into_iter()
+                            .filter(|(_, n, max)| n > max)
+                            .map(|(color, n, max)| format!("{} {}>{}", n, color, max))
+                            .collect::<Vec<_>>();
+                            step.with("impossible", format!("draw {}", i + 1))
+                                .with("over", over.join(", "))
+                        }
+                        None => step.with("possible", "yes"),
+                    },
+                    Part::Two => with_minimal_bag(step, game),
+                }
             })
-            .count();
+            .collect()
+    }
+}
+
+impl Query for Day2 {
+    const QUERIES: &'static [(&'static str, &'static str)] = &[("game", "<id>")];
+
+    fn query(games: &Self::Model, _name: &str, arg: &str) -> Result<Step, String> {
+        let game = arg
+            .parse::<usize>()
+            .ok()
+            .and_then(|id| games.games.iter().find(|g| g.id == id))
+            .ok_or_else(|| format!("no game {}", arg))?;
+        Ok(with_minimal_bag(Step::new().with("game", game.id), game))
+    }
+}
+
+// The fewest cubes of each color the game could be played with, and
+// their power.
+fn with_minimal_bag(step: Step, game: &Game) -> Step {
+    let bag = game.minimal_bag();
+    step.with("red", bag.red)
+        .with("green", bag.green)
+        .with("blue", bag.blue)
+        .with("power", bag.red * bag.green * bag.blue)
+}
+
+impl Generator for Day2 {
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut input = String::new();
+        for id in 1..=size {
+            let draws: Vec<String> = (0..rng.index(6) + 1)
+                .map(|_| {
+                    let mut colors = ["red", "green", "blue"];
+                    rng.shuffle(&mut colors);
+                    colors[..rng.index(3) + 1]
+                        .iter()
+                        .map(|color| format!("{} {}", rng.range(1..21), color))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .collect();
+            input.push_str(&format!("Game {}: {}\n", id, draws.join("; ")));
+        }
+        input
+    }
+}
+
+pub fn parse(input: &str) -> Result<Vec<Game>, PuzzleError> {
+    parse::lines(input)
+        .map(|l| l.text.parse::<Game>().map_err(|e| l.within(l.text, e)))
+        .collect()
+}
+
+// The cubes in the bag for part one, unless the config says otherwise.
+pub const BAG: Draw = Draw {
+    red: 12,
+    green: 13,
+    blue: 14,
+};
+
+pub fn part_one(games: &[Game], bag: &Draw) -> usize {
+    let mut sum = 0;
+
+    for game in games {
+        let invalid = game.draws.iter().filter(|d| !d.is_valid(bag)).count();
 
         if invalid == 0 {
-            sum += game.0;
+            sum += game.id;
         }
     }
     sum
 }
 
-pub fn part_two(input: &str) -> isize {
+pub fn part_two(games: &[Game]) -> isize {
     let mut sum = 0;
 
-    for line in input.lines().filter(|l| !l.trim().is_empty()) {
-        let (_, draws) = line.split_once(':').unwrap();
-
-        let m = Draw::default();
-        let res = draws.split(';').fold(m, |rm, draw| {
-            let d = draw.trim().parse::<Draw>().unwrap();
-            d.upper_bound(&rm)
-        });
+    for game in games {
+        let res = game.minimal_bag();
         sum += res.red * res.blue * res.green;
     }
     sum
@@ -109,7 +294,9 @@ pub fn part_two(input: &str) -> isize {
 
 #[cfg(test)]
 mod tests {
-    use super::{part_one, part_two};
+    use super::{parse, part_one, part_two, Day2, BAG};
+    use crate::params::Params;
+    use crate::solution::{Explain, Part, Solution};
 
     #[test]
     fn test_puzzle_example() {
@@ -120,7 +307,35 @@ Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
 Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
 Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
 ";
-        assert_eq!(8, part_one(input));
-        assert_eq!(2286, part_two(input));
+        let games = parse(input).unwrap();
+        assert_eq!(8, part_one(&games, &BAG));
+        assert_eq!(2286, part_two(&games));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = "Game 1: 3 blue, 4 red\nGame 2: 1 blue; 6 purple\n";
+        let span = parse(input).unwrap_err().span.unwrap();
+        assert_eq!((2, 19), (span.line, span.column));
+        assert_eq!("purple", span.snippet);
+    }
+
+    #[test]
+    fn test_explain() {
+        let games = Day2::parse(include_str!("../examples/day2/example.txt")).unwrap();
+        let steps = Day2::explain(&games, Part::One);
+        assert_eq!("game=1 possible=yes", steps[0].to_string());
+        assert_eq!(
+            "game=4 impossible=\"draw 3\" over=\"14 red>12, 15 blue>14\"",
+            steps[3].to_string()
+        );
+    }
+
+    #[test]
+    fn test_params() {
+        // Game 3 draws 20 red cubes.
+        let input = include_str!("../examples/day2/example.txt");
+        let games = Day2::parse_with(input, &Params::new().with("red", 20)).unwrap();
+        assert_eq!(Ok(11), Day2::part_one(&games));
     }
 }
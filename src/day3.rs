@@ -1,65 +1,101 @@
 use std::collections::{HashMap, HashSet};
 use std::ops;
 
-struct Schematic<'a> {
-    map: Vec<&'a str>,
-    num_row: isize,
-    num_col: isize,
+use crate::error::PuzzleError;
+use crate::grid;
+use crate::parse::{self, Line};
+use crate::rng::Rng;
+use crate::solution::{Explain, Generator, Lint, Part, Solution, Step};
+
+pub struct Schematic {
+    map: Vec<String>,
+    line_nos: Vec<usize>,
+    num_row: usize,
+    num_col: usize,
 }
 
 type GearAdjList = HashMap<(usize, usize), Vec<u64>>;
 
-impl<'a> Schematic<'a> {
-    fn new(input: &'a str) -> Self {
-        let map: Vec<_> = input.lines().filter(|l| !l.is_empty()).collect();
-        let num_row = map.len() as isize;
-        let num_col = map[0].len() as isize;
-        Schematic {
-            map,
-            num_row,
-            num_col,
+impl Schematic {
+    pub fn new(input: &str) -> Result<Self, PuzzleError> {
+        let lines: Vec<_> = parse::lines(input).collect();
+        let first = lines
+            .first()
+            .ok_or_else(|| PuzzleError::new("empty schematic"))?;
+
+        for line in &lines {
+            if let Some((i, _)) = line.text.char_indices().find(|(_, c)| !c.is_ascii()) {
+                return Err(line.error(&line.text[i..], "unexpected character"));
+            }
+            if line.text.len() != first.text.len() {
+                return Err(line.error(
+                    line.text,
+                    format!("expected a row of width {}", first.text.len()),
+                ));
+            }
         }
+
+        Ok(Schematic {
+            map: lines.iter().map(|l| l.text.to_string()).collect(),
+            line_nos: lines.iter().map(|l| l.no).collect(),
+            num_row: lines.len(),
+            num_col: first.text.len(),
+        })
     }
 
-    fn adj_to_symbol(&self, row: usize, col: usize) -> bool {
-        let irow = row as isize;
-        let icol = col as isize;
-        let row_range = 0..self.num_row;
-        let col_range = 0..self.num_col;
-
-        for dr in [-1, 0, 1] {
-            for dc in [-1, 0, 1] {
-                let (nrow, ncol) = (irow + dr, icol + dc);
-                if row_range.contains(&nrow) && col_range.contains(&ncol) {
-                    let c = self.map[nrow as usize].chars().nth(ncol as usize).unwrap();
-                    if !c.is_ascii_digit() && c != '.' {
-                        return true;
-                    }
-                }
-            }
+    pub fn rows(&self) -> &[String] {
+        &self.map
+    }
+
+    fn line(&self, row: usize) -> Line<'_> {
+        Line {
+            no: self.line_nos[row],
+            text: &self.map[row],
         }
-        false
-    }
-
-    fn get_adj_gears(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
-        let irow = row as isize;
-        let icol = col as isize;
-        let row_range = 0..self.num_row;
-        let col_range = 0..self.num_col;
-
-        let mut gears = Vec::new();
-        for dr in [-1, 0, 1] {
-            for dc in [-1, 0, 1] {
-                let (nrow, ncol) = (irow + dr, icol + dc);
-                if row_range.contains(&nrow) && col_range.contains(&ncol) {
-                    let c = self.map[nrow as usize].chars().nth(ncol as usize).unwrap();
-                    if c == '*' {
-                        gears.push((nrow as usize, ncol as usize));
-                    }
+    }
+
+    fn around(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize, u8)> + '_ {
+        grid::around(row, col, self.num_row, self.num_col)
+            .map(|(r, c)| (r, c, self.map[r].as_bytes()[c]))
+    }
+
+    // The row and columns of every number, in reading order.
+    fn numbers(&self) -> Vec<(usize, ops::Range<usize>)> {
+        let mut numbers = vec![];
+        for (ri, row) in self.map.iter().enumerate() {
+            let mut digits = row
+                .char_indices()
+                .filter(|(_, c)| c.is_ascii_digit())
+                .peekable();
+
+            while let Some((ci, _)) = digits.next() {
+                let start = ci;
+
+                let mut end = ci;
+                while digits.next_if(|(nci, _)| *nci == end + 1).is_some() {
+                    end += 1;
                 }
+                end += 1;
+                numbers.push((ri, start..end));
             }
         }
-        gears
+        numbers
+    }
+
+    fn number(&self, row: usize, cols: ops::Range<usize>) -> Result<u64, PuzzleError> {
+        self.line(row).number(&self.map[row][cols])
+    }
+
+    pub fn adj_to_symbol(&self, row: usize, col: usize) -> bool {
+        self.around(row, col)
+            .any(|(_, _, c)| !c.is_ascii_digit() && c != b'.')
+    }
+
+    pub fn get_adj_gears(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        self.around(row, col)
+            .filter(|&(_, _, c)| c == b'*')
+            .map(|(r, c, _)| (r, c))
+            .collect()
     }
 
     fn mark_adj_gears(
@@ -85,70 +121,165 @@ impl<'a> Schematic<'a> {
     }
 }
 
-pub fn part_one(input: &str) -> u64 {
-    let schem = Schematic::new(input);
-    let mut sum = 0;
+pub struct Day3;
 
-    for (ri, row) in schem.map.iter().enumerate() {
-        let mut digits = row
-            .char_indices()
-            .filter(|(_, c)| c.is_ascii_digit())
-            .peekable();
+impl Solution for Day3 {
+    const YEAR: u32 = 2023;
+    const DAY: u32 = 3;
+    const TITLE: &'static str = "Gear Ratios";
 
-        while let Some((ci, _)) = digits.next() {
-            let start = ci;
+    type Model = Schematic;
+    type PartOne = u64;
+    type PartTwo = u64;
 
-            let mut end = ci;
-            while digits.next_if(|(nci, _)| *nci == end + 1).is_some() {
-                end += 1;
-            }
-            end += 1;
+    fn parse(input: &str) -> Result<Self::Model, PuzzleError> {
+        Schematic::new(input)
+    }
+
+    fn part_one(schem: &Self::Model) -> Result<u64, PuzzleError> {
+        part_one(schem)
+    }
 
-            if (start..end).any(|ci| schem.adj_to_symbol(ri, ci)) {
-                let dig_str = &row[start..end];
-                sum += dig_str.parse::<u64>().unwrap();
+    fn part_two(schem: &Self::Model) -> Result<u64, PuzzleError> {
+        part_two(schem)
+    }
+}
+
+impl Lint for Day3 {
+    // Every row has the width of the first and only ASCII characters.
+    fn lint(input: &str) -> Vec<PuzzleError> {
+        let lines: Vec<_> = parse::lines(input).collect();
+        let Some(first) = lines.first() else {
+            return vec![PuzzleError::new("empty schematic")];
+        };
+        let mut errors = vec![];
+        for line in &lines {
+            let text = line.text;
+            errors.extend(
+                text.char_indices()
+                    .filter(|(_, c)| !c.is_ascii())
+                    .map(|(i, c)| line.error(&text[i..i + c.len_utf8()], "unexpected character")),
+            );
+            if text.len() != first.text.len() {
+                errors.push(line.error(
+                    text,
+                    format!(
+                        "expected a row of width {} as on line {}",
+                        first.text.len(),
+                        first.no
+                    ),
+                ));
             }
         }
+        errors
     }
-    sum
 }
 
-pub fn part_two(input: &str) -> u64 {
-    let schem = Schematic::new(input);
-    let mut gear_adj = GearAdjList::new();
-
-    for (ri, row) in schem.map.iter().enumerate() {
-        let mut digits = row
-            .char_indices()
-            .filter(|(_, c)| c.is_ascii_digit())
-            .peekable();
-
-        while let Some((ci, _)) = digits.next() {
-            let start = ci;
+impl Explain for Day3 {
+    // Part one gives every number and whether it is a part number. Part
+    // two gives every `*` next to a number, with the numbers around it
+    // and, for the gears among them, the ratio.
+    fn explain(schem: &Self::Model, part: Part) -> Vec<Step> {
+        let at = |row: usize, col: usize| {
+            Step::new()
+                .with("line", schem.line_nos[row])
+                .with("column", col + 1)
+        };
+        match part {
+            Part::One => schem
+                .numbers()
+                .into_iter()
+                .map(|(ri, cols)| {
+                    let step = at(ri, cols.start).with("number", &schem.map[ri][cols.clone()]);
+                    let adj = cols.clone().any(|ci| schem.adj_to_symbol(ri, ci));
+                    step.with("part", if adj { "yes" } else { "no" })
+                })
+                .collect(),
+            Part::Two => {
+                let gear_adj = match gear_adj(schem) {
+                    Ok(gear_adj) => gear_adj,
+                    Err(e) => return vec![Step::new().with("error", e.message)],
+                };
+                let mut stars: Vec<_> = gear_adj.into_iter().collect();
+                stars.sort();
+                stars
+                    .into_iter()
+                    .map(|((ri, ci), nums)| {
+                        let shown: Vec<String> = nums.iter().map(u64::to_string).collect();
+                        let step = at(ri, ci).with("numbers", shown.join(" "));
+                        match nums[..] {
+                            [a, b] => step.with("ratio", a * b),
+                            _ => step.with("gear", "no"),
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+}
 
-            let mut end = ci;
-            while digits.next_if(|(nci, _)| *nci == end + 1).is_some() {
-                end += 1;
+impl Generator for Day3 {
+    // A square schematic `size` wide with numbers of up to three
+    // digits and a sprinkling of symbols.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        const SYMBOLS: &[u8] = b"*#+$/@%=&-";
+        let width = size.max(1);
+        let mut input = String::new();
+        for _ in 0..width {
+            let mut row = String::new();
+            while row.len() < width {
+                let after_number = row.ends_with(|c: char| c.is_ascii_digit());
+                if !after_number && rng.chance(0.2) {
+                    let len = (rng.index(3) + 1).min(width - row.len());
+                    row.push(char::from(b'1' + rng.index(9) as u8));
+                    (1..len).for_each(|_| row.push(char::from(b'0' + rng.index(10) as u8)));
+                } else if rng.chance(0.15) {
+                    row.push(char::from(*rng.pick(SYMBOLS)));
+                } else {
+                    row.push('.');
+                }
             }
-            end += 1;
-            let num = row[start..end].parse::<u64>().unwrap();
-            schem.mark_adj_gears(ri, start..end, num, &mut gear_adj);
+            input.push_str(&row);
+            input.push('\n');
         }
+        input
     }
+}
 
-    gear_adj.values().fold(0, |sum, adj_nums| {
+pub fn part_one(schem: &Schematic) -> Result<u64, PuzzleError> {
+    let mut sum = 0;
+    for (ri, cols) in schem.numbers() {
+        if cols.clone().any(|ci| schem.adj_to_symbol(ri, ci)) {
+            sum += schem.number(ri, cols)?;
+        }
+    }
+    Ok(sum)
+}
+
+fn gear_adj(schem: &Schematic) -> Result<GearAdjList, PuzzleError> {
+    let mut gear_adj = GearAdjList::new();
+    for (ri, cols) in schem.numbers() {
+        let num = schem.number(ri, cols.clone())?;
+        schem.mark_adj_gears(ri, cols, num, &mut gear_adj);
+    }
+    Ok(gear_adj)
+}
+
+pub fn part_two(schem: &Schematic) -> Result<u64, PuzzleError> {
+    Ok(gear_adj(schem)?.values().fold(0, |sum, adj_nums| {
         if adj_nums.len() == 2 {
             let ratio = adj_nums[0] * adj_nums[1];
             sum + ratio
         } else {
             sum
         }
-    })
+    }))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{part_one, part_two};
+    use super::{part_one, part_two, Day3, Schematic};
+    use crate::solution::{Explain, Part};
 
     #[test]
     fn test_puzzle_example() {
@@ -164,7 +295,26 @@ mod tests {
 ...$.*....
 .664.598..
 ";
-        assert_eq!(4361, part_one(input));
-        assert_eq!(467835, part_two(input));
+        let schem = Schematic::new(input).unwrap();
+        assert_eq!(Ok(4361), part_one(&schem));
+        assert_eq!(Ok(467835), part_two(&schem));
+    }
+
+    #[test]
+    fn test_explain() {
+        let schem = Schematic::new(include_str!("../examples/day3/example.txt")).unwrap();
+        let steps = Day3::explain(&schem, Part::One);
+        assert_eq!("line=1 column=1 number=467 part=yes", steps[0].to_string());
+        assert_eq!("line=1 column=6 number=114 part=no", steps[1].to_string());
+
+        let steps = Day3::explain(&schem, Part::Two);
+        assert_eq!(
+            vec![
+                "line=2 column=4 numbers=\"467 35\" ratio=16345",
+                "line=5 column=4 numbers=617 gear=no",
+                "line=9 column=6 numbers=\"755 598\" ratio=451490",
+            ],
+            steps.iter().map(|s| s.to_string()).collect::<Vec<_>>()
+        );
     }
 }
@@ -1,31 +1,195 @@
 use std::collections::HashSet;
 
-fn collect_matches(input: &str) -> Vec<u64> {
-    input
-        .lines()
-        .filter(|l| !l.trim().is_empty())
-        .map(|line| {
-            let (_, numbers) = line.split_once(':').unwrap();
+use crate::error::PuzzleError;
+use crate::parse;
+use crate::rng::Rng;
+use crate::solution::{Explain, Generator, Lint, Part, Reference, Solution, Step};
+
+pub struct Card {
+    winning: HashSet<u64>,
+    mine: Vec<u64>,
+}
+
+impl Card {
+    pub fn winning(&self) -> &HashSet<u64> {
+        &self.winning
+    }
+
+    pub fn mine(&self) -> &[u64] {
+        &self.mine
+    }
+
+    pub fn matches(&self) -> u64 {
+        self.mine
+            .iter()
+            .filter(|num| self.winning.contains(num))
+            .count() as u64
+    }
+}
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    const YEAR: u32 = 2023;
+    const DAY: u32 = 4;
+    const TITLE: &'static str = "Scratchcards";
+
+    type Model = Vec<Card>;
+    type PartOne = u64;
+    type PartTwo = u64;
+
+    fn parse(input: &str) -> Result<Self::Model, PuzzleError> {
+        parse(input)
+    }
+
+    fn part_one(cards: &Self::Model) -> Result<u64, PuzzleError> {
+        Ok(part_one(cards))
+    }
+
+    fn part_two(cards: &Self::Model) -> Result<u64, PuzzleError> {
+        part_two(cards)
+    }
+}
+
+impl Lint for Day4 {
+    // Every card is checked for its separators and every number on it,
+    // so that all bad cards show up at once.
+    fn lint(input: &str) -> Vec<PuzzleError> {
+        let mut errors = vec![];
+        for line in parse::lines(input) {
+            let (card, numbers) = match line.split_once(line.text, ':') {
+                Ok(parts) => parts,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+            match card.trim().strip_prefix("Card ") {
+                Some(id) => errors.extend(line.number::<usize>(id.trim()).err()),
+                None => errors.push(line.error(card, "expected 'Card <id>'")),
+            }
+            let (winning, mine) = match line.split_once(numbers, '|') {
+                Ok(parts) => parts,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+            for num in winning.split_whitespace().chain(mine.split_whitespace()) {
+                errors.extend(line.number::<u64>(num).err());
+            }
+        }
+        errors
+    }
+}
 
-            let (winning_nums, my_nums) = numbers.trim().split_once('|').unwrap();
+impl Explain for Day4 {
+    // Part one gives the matches and points of each card. Part two also
+    // gives how many copies of each card end up scratched, and from the
+    // dp table, how many cards one copy of it yields in all.
+    fn explain(cards: &Self::Model, part: Part) -> Vec<Step> {
+        let matches = collect_matches(cards);
+        let steps = matches
+            .iter()
+            .enumerate()
+            .map(|(i, &m)| Step::new().with("card", i + 1).with("matches", m));
+        match part {
+            Part::One => steps
+                .zip(&matches)
+                .map(|(step, &m)| step.with("points", if m > 0 { 1u64 << (m - 1) } else { 0 }))
+                .collect(),
+            Part::Two => {
+                let yields = match dp_table(&matches) {
+                    Ok(yields) => yields,
+                    Err(e) => return vec![Step::new().with("error", e.message)],
+                };
+                let mut copies = vec![1; matches.len()];
+                for i in 0..matches.len() {
+                    for j in i + 1..=i + matches[i] as usize {
+                        copies[j] += copies[i];
+                    }
+                }
+                steps
+                    .zip(copies.iter().zip(&yields))
+                    .map(|(step, (c, y))| step.with("copies", c).with("yields", y))
+                    .collect()
+            }
+        }
+    }
+}
 
-            let winning_nums: HashSet<_> = winning_nums
-                .split_whitespace()
-                .map(|num| num.parse::<u64>().unwrap())
+impl Generator for Day4 {
+    // Cards never win copies of cards past the end of the table.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut input = String::new();
+        for id in 1..=size {
+            let mut pool: Vec<u64> = (1..100).collect();
+            rng.shuffle(&mut pool);
+            let matches = rng.index((size - id).min(5) + 1);
+            let winning = &pool[..5];
+            let mut mine: Vec<u64> = winning[..matches]
+                .iter()
+                .chain(&pool[5..13 - matches])
+                .copied()
                 .collect();
+            rng.shuffle(&mut mine);
+            let show = |nums: &[u64]| {
+                nums.iter()
+                    .map(|n| format!("{:>2}", n))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+            input.push_str(&format!(
+                "Card {:>3}: {} | {}\n",
+                id,
+                show(winning),
+                show(&mine)
+            ));
+        }
+        input
+    }
+}
+
+impl Reference for Day4 {
+    // Scratch every copy of every card one at a time.
+    fn naive_part_two(cards: &Self::Model) -> Option<u64> {
+        let matches = collect_matches(cards);
+        let mut pile: Vec<usize> = (0..cards.len()).collect();
+        let mut scratched = 0;
+        while let Some(card) = pile.pop() {
+            scratched += 1;
+            if scratched > 10_000_000 {
+                return None;
+            }
+            let wins = matches[card] as usize;
+            pile.extend((card + 1..=card + wins).filter(|&c| c < cards.len()));
+        }
+        Some(scratched)
+    }
+}
 
-            my_nums
-                .split_whitespace()
-                .map(|num| num.parse::<u64>().unwrap())
-                .filter(|num| winning_nums.contains(num))
-                .count() as u64
+pub fn parse(input: &str) -> Result<Vec<Card>, PuzzleError> {
+    parse::lines(input)
+        .map(|line| {
+            let (_, numbers) = line.split_once(line.text, ':')?;
+
+            let (winning_nums, my_nums) = line.split_once(numbers.trim(), '|')?;
+
+            let winning = line.numbers::<u64>(winning_nums)?.into_iter().collect();
+            let mine = line.numbers::<u64>(my_nums)?;
+
+            Ok(Card { winning, mine })
         })
         .collect()
 }
 
-pub fn part_one(input: &str) -> u64 {
+fn collect_matches(cards: &[Card]) -> Vec<u64> {
+    cards.iter().map(Card::matches).collect()
+}
+
+pub fn part_one(cards: &[Card]) -> u64 {
     let mut sum = 0;
-    for matches in collect_matches(input) {
+    for matches in collect_matches(cards) {
         if matches > 0 {
             sum += 2u64.pow(matches as u32 - 1);
         }
@@ -33,22 +197,33 @@ pub fn part_one(input: &str) -> u64 {
     sum
 }
 
-pub fn part_two(input: &str) -> u64 {
-    let matches = collect_matches(input);
+// How many cards, itself included, one copy of each card yields.
+fn dp_table(matches: &[u64]) -> Result<Vec<u64>, PuzzleError> {
     let mut dp_table = vec![0; matches.len()];
 
     for dpi in (0..matches.len()).rev() {
         let wins = matches[dpi];
+        if dpi + wins as usize >= matches.len() {
+            return Err(PuzzleError::new(format!(
+                "card {} wins copies of cards past the end of the table",
+                dpi + 1
+            )));
+        }
         let accu_wins: u64 = (1..=wins).map(|i| dp_table[dpi + i as usize]).sum();
         // card itself + accumulated wins
         dp_table[dpi] = 1 + accu_wins;
     }
-    dp_table.iter().sum()
+    Ok(dp_table)
+}
+
+pub fn part_two(cards: &[Card]) -> Result<u64, PuzzleError> {
+    Ok(dp_table(&collect_matches(cards))?.iter().sum())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{part_one, part_two};
+    use super::{parse, part_one, part_two, Day4};
+    use crate::solution::Lint;
 
     #[test]
     fn test_puzzle_example() {
@@ -60,7 +235,21 @@ Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
 Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
 Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
 ";
-        assert_eq!(13, part_one(input));
-        assert_eq!(30, part_two(input));
+        let cards = parse(input).unwrap();
+        assert_eq!(13, part_one(&cards));
+        assert_eq!(Ok(30), part_two(&cards));
+    }
+
+    #[test]
+    fn test_lint_every_card() {
+        let errors = Day4::lint("Card 1: 41 48 | 83 x\nCard 2: 1 2 3\nCard y: 1 | 2\n");
+        let messages: Vec<_> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(3, messages.len(), "{:?}", messages);
+        let lines: Vec<_> = errors
+            .iter()
+            .map(|e| e.span.as_ref().unwrap().line)
+            .collect();
+        assert_eq!(vec![1, 2, 3], lines);
+        assert!(Day4::lint(include_str!("../examples/day4/example.txt")).is_empty());
     }
 }
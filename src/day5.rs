@@ -1,93 +1,122 @@
 use std::ops;
 
-type Range = ops::Range<u64>;
+use crate::error::PuzzleError;
+use crate::parse::{self, Line};
+use crate::ranges;
+use crate::rng::Rng;
+use crate::solution::{Explain, Generator, Lint, Part, Query, Reference, Solution, Step};
+
+pub type Range = ops::Range<u64>;
 
 // Range and whether a mapping was found for it or not
 struct MappedRange(Range, bool);
 
-fn map_range(kr: &Range, mr_and_offset: (&Range, isize)) -> Vec<MappedRange> {
-    let mut mapped = vec![];
+fn map_range(kr: &Range, mr_and_offset: (&Range, i128)) -> Vec<MappedRange> {
     let (mr, offset) = mr_and_offset;
 
     let apply_offset = |r: Range| {
-        let start = (r.start as isize + offset) as u64;
-        let end = (r.end as isize + offset) as u64;
+        let start = (r.start as i128 + offset) as u64;
+        let end = (r.end as i128 + offset) as u64;
         MappedRange(start..end, true)
     };
 
-    let one_to_one = |r: Range| MappedRange(r, false);
-
-    if kr.end <= mr.start || mr.end <= kr.start {
-        // disjoint ranges
-        mapped.push(one_to_one(kr.clone()));
-    } else {
-        // intersecting ranges: [ks, ke) and [ms, me)
-        if kr.start <= mr.start {
-            // ks.....
-            //    ms..
-            if kr.start != mr.start {
-                // [ks, ms) maps one-to-one
-                mapped.push(one_to_one(kr.start..mr.start));
-            }
+    // The part of the key range inside the map range moves by the
+    // offset, the rest maps one-to-one for now.
+    let (inside, outside) = ranges::split(kr, mr);
+    inside
+        .map(apply_offset)
+        .into_iter()
+        .chain(outside.into_iter().map(|r| MappedRange(r, false)))
+        .collect()
+}
 
-            if kr.end <= mr.end {
-                // ks......ke
-                //    ms...me
-                mapped.push(apply_offset(mr.start..kr.end));
-                // ke..me is ignored as it's out of key's range
-            } else {
-                // ks..........ke
-                //    ms.....me
-                mapped.push(apply_offset(mr.start..mr.end));
-                // [me, ke) maps one-to-one
-                mapped.push(one_to_one(mr.end..kr.end));
-            }
-        } else {
-            //    ks....
-            // ms.......
-            if kr.end <= mr.end {
-                //    ks....ke
-                // ms.......me
-                mapped.push(apply_offset(kr.start..kr.end));
-                // ke..me is out of key's range
-            } else {
-                //    ks.......ke
-                // ms......me
-                mapped.push(apply_offset(kr.start..mr.end));
-                // [me, ke) maps one-to-one
-                mapped.push(one_to_one(mr.end..kr.end));
+// One map of the almanac (e.g. seed-to-soil map) as source ranges
+// and the offset to apply to keys falling into them. The offset is
+// wider than the keys so that it can span the whole u64 range.
+pub type Map = Vec<(Range, i128)>;
+
+pub struct Almanac {
+    seeds: Vec<u64>,
+    maps: Vec<Map>,
+}
+
+impl Almanac {
+    pub fn new(input: &str) -> Result<Self, PuzzleError> {
+        let mut lines = parse::all_lines(input);
+
+        let seeds_line = lines
+            .by_ref()
+            .find(|l| !l.text.trim().is_empty())
+            .ok_or_else(|| PuzzleError::new("missing seeds"))?;
+        let (_, seeds) = seeds_line.split_once(seeds_line.text, ':')?;
+        let seeds = seeds_line.numbers::<u64>(seeds)?;
+
+        let mut maps = vec![];
+        lines.by_ref().next();
+        while let Some(header) = lines.by_ref().next() {
+            if !header.text.trim_end().ends_with("map:") {
+                return Err(header.error(header.text, "expected a map header"));
             }
+            maps.push(parse_map(lines.by_ref())?);
         }
+
+        Ok(Almanac { seeds, maps })
+    }
+
+    pub fn seeds(&self) -> &[u64] {
+        &self.seeds
+    }
+
+    pub fn maps(&self) -> &[Map] {
+        &self.maps
+    }
+
+    pub fn lowest_location(&self, mut ranges: Vec<Range>) -> Result<u64, PuzzleError> {
+        for map in &self.maps {
+            ranges = apply_map(ranges, map);
+        }
+        ranges
+            .iter()
+            .map(|r| r.start)
+            .min()
+            .ok_or_else(|| PuzzleError::new("no seeds to plant"))
     }
-    mapped
 }
 
-// Apply a map (e.g. seed-to-soil map) to a given set of key
-// ranges. lines is expected to point to the first entry in the map.
-fn apply_map<'a, I>(mut key_ranges: Vec<Range>, lines: &mut I) -> Vec<Range>
+// lines is expected to point to the first entry in the map.
+fn parse_map<'a, I>(lines: &mut I) -> Result<Map, PuzzleError>
 where
-    I: Iterator<Item = &'a str>,
+    I: Iterator<Item = Line<'a>>,
 {
-    let map_ranges: Vec<_> = lines
-        .take_while(|l| !l.trim().is_empty())
+    lines
+        .take_while(|l| !l.text.trim().is_empty())
         .map(|l| {
-            let nums: Vec<u64> = l
-                .split_whitespace()
-                .map(|n| n.parse::<u64>().unwrap())
-                .collect();
-            let offset = nums[0] as isize - nums[1] as isize;
-            let map_range = nums[1]..nums[1] + nums[2];
-            (map_range, offset)
+            let nums = l.numbers::<u64>(l.text)?;
+            if nums.len() != 3 {
+                return Err(l.error(l.text, "expected destination, source and length"));
+            }
+            // Both ends must be numbers, as must their mapped values.
+            let (Some(end), Some(_)) = (nums[1].checked_add(nums[2]), nums[0].checked_add(nums[2]))
+            else {
+                return Err(l.error(l.text, "range runs past the largest number"));
+            };
+            let offset = nums[0] as i128 - nums[1] as i128;
+            let map_range = nums[1]..end;
+            Ok((map_range, offset))
         })
-        .collect();
+        .collect()
+}
 
+// Apply a map (e.g. seed-to-soil map) to a given set of key
+// ranges.
+fn apply_map(mut key_ranges: Vec<Range>, map: &Map) -> Vec<Range> {
     let mut mapped = vec![];
 
-    for (mr, offset) in map_ranges {
+    for (mr, offset) in map {
         let mut next_keys = vec![];
 
         for kr in key_ranges {
-            let output = map_range(&kr, (&mr, offset));
+            let output = map_range(&kr, (mr, *offset));
             for mapped_range in output {
                 if mapped_range.1 {
                     // mapping found
@@ -108,64 +137,273 @@ where
     mapped
 }
 
-pub fn part_one(input: &str) -> u64 {
-    let mut lines = input.lines();
+pub struct Day5;
+
+impl Solution for Day5 {
+    const YEAR: u32 = 2023;
+    const DAY: u32 = 5;
+    const TITLE: &'static str = "If You Give A Seed A Fertilizer";
+
+    type Model = Almanac;
+    type PartOne = u64;
+    type PartTwo = u64;
+
+    fn parse(input: &str) -> Result<Self::Model, PuzzleError> {
+        Almanac::new(input)
+    }
+
+    fn part_one(almanac: &Self::Model) -> Result<u64, PuzzleError> {
+        part_one(almanac)
+    }
+
+    fn part_two(almanac: &Self::Model) -> Result<u64, PuzzleError> {
+        part_two(almanac)
+    }
+}
+
+impl Lint for Day5 {
+    // Besides the shape of the almanac, checks that the seeds pair up
+    // into ranges and that no two source ranges of a map overlap.
+    fn lint(input: &str) -> Vec<PuzzleError> {
+        let mut lines = parse::all_lines(input);
+        let Some(seeds_line) = lines.by_ref().find(|l| !l.text.trim().is_empty()) else {
+            return vec![PuzzleError::new("missing seeds")];
+        };
+        let mut errors = vec![];
+        let seeds = seeds_line
+            .split_once(seeds_line.text, ':')
+            .and_then(|(_, seeds)| seeds_line.numbers::<u64>(seeds));
+        match seeds {
+            Ok(seeds) if !seeds.len().is_multiple_of(2) => {
+                errors.push(seeds_line.error(seeds_line.text, "seeds do not pair up into ranges"))
+            }
+            Ok(_) => (),
+            Err(e) => errors.push(e),
+        }
+
+        let mut in_map = false;
+        let mut entries = vec![];
+        for line in lines {
+            if line.text.trim().is_empty() {
+                lint_overlaps(&mut entries, &mut errors);
+                in_map = false;
+            } else if line.text.trim_end().ends_with("map:") {
+                lint_overlaps(&mut entries, &mut errors);
+                in_map = true;
+            } else if !in_map {
+                errors.push(line.error(line.text, "expected a map header"));
+            } else {
+                match line.numbers::<u64>(line.text) {
+                    Ok(nums) if nums.len() == 3 => {
+                        match (nums[1].checked_add(nums[2]), nums[0].checked_add(nums[2])) {
+                            (Some(end), Some(_)) => entries.push((line, nums[1]..end)),
+                            _ => errors
+                                .push(line.error(line.text, "range runs past the largest number")),
+                        }
+                    }
+                    Ok(_) => errors
+                        .push(line.error(line.text, "expected destination, source and length")),
+                    Err(e) => errors.push(e),
+                }
+            }
+        }
+        lint_overlaps(&mut entries, &mut errors);
+        errors
+    }
+}
+
+// Report the entries of a map whose source range overlaps an earlier
+// one, and start over for the next map.
+fn lint_overlaps(entries: &mut Vec<(Line, Range)>, errors: &mut Vec<PuzzleError>) {
+    entries.sort_by_key(|(_, range)| range.start);
+    let mut furthest: Option<&(Line, Range)> = None;
+    for entry in entries.iter() {
+        let (line, range) = entry;
+        match furthest {
+            Some((other, reach)) if ranges::overlaps(range, reach) => errors.push(line.error(
+                line.text,
+                format!("source range overlaps the one on line {}", other.no),
+            )),
+            _ => (),
+        }
+        if furthest.is_none_or(|(_, reach)| reach.end < range.end) {
+            furthest = Some(entry);
+        }
+    }
+    entries.clear();
+}
 
-    let (_, seeds) = lines
-        .by_ref()
-        .find(|l| !l.trim().is_empty())
-        .unwrap()
-        .split_once(':')
-        .unwrap();
+impl Explain for Day5 {
+    // Part one follows each seed through the maps to its location, part
+    // two gives the lowest location of each seed range.
+    fn explain(almanac: &Self::Model, part: Part) -> Vec<Step> {
+        match part {
+            Part::One => almanac
+                .seeds
+                .iter()
+                .map(|&seed| follow_seed(almanac, seed))
+                .collect(),
+            Part::Two => almanac
+                .seeds
+                .chunks_exact(2)
+                .map(|pair| {
+                    let Some(end) = pair[0].checked_add(pair[1]) else {
+                        return Step::new()
+                            .with("seeds", format!("{} {}", pair[0], pair[1]))
+                            .with("error", "range runs past the largest number");
+                    };
+                    let range = pair[0]..end;
+                    let step = Step::new().with("seeds", format!("{}..{}", range.start, range.end));
+                    match almanac.lowest_location(vec![range]) {
+                        Ok(location) => step.with("lowest", location),
+                        Err(e) => step.with("error", e.message),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Query for Day5 {
+    const QUERIES: &'static [(&'static str, &'static str)] = &[("seed", "<number>")];
 
-    let seeds: Vec<u64> = seeds
-        .split_whitespace()
-        .map(|n| n.parse::<u64>().unwrap())
-        .collect();
+    fn query(almanac: &Self::Model, _name: &str, arg: &str) -> Result<Step, String> {
+        let seed = arg.parse().map_err(|_| format!("invalid seed '{}'", arg))?;
+        Ok(follow_seed(almanac, seed))
+    }
+}
 
-    let mut ranges: Vec<Range> = seeds.iter().map(|s| *s..(*s + 1)).collect();
+// A seed through every map to its location, listed or not.
+fn follow_seed(almanac: &Almanac, seed: u64) -> Step {
+    let mut key = seed;
+    let mut chain = vec![key.to_string()];
+    for map in &almanac.maps {
+        key = map_key(map, key);
+        chain.push(key.to_string());
+    }
+    Step::new()
+        .with("seed", seed)
+        .with("chain", chain.join(" -> "))
+        .with("location", key)
+}
+
+impl Generator for Day5 {
+    // `size` seed ranges and as many entries in each of the seven maps.
+    // The source ranges of a map do not overlap, nor do its destinations.
+    // Numbers grow with the size, up to the 32 bits of real inputs, so
+    // that small inputs stay small enough to check seed by seed.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        const NAMES: [&str; 7] = [
+            "seed-to-soil",
+            "soil-to-fertilizer",
+            "fertilizer-to-water",
+            "water-to-light",
+            "light-to-temperature",
+            "temperature-to-humidity",
+            "humidity-to-location",
+        ];
+        let size = size.max(1) as u64;
+        let limit = size.saturating_pow(3).clamp(1000, 4_000_000_000);
+        let span = limit / size;
+
+        let seeds: Vec<String> = (0..size)
+            .flat_map(|_| [rng.range(0..limit), rng.range(1..span / 4 + 2)])
+            .map(|n| n.to_string())
+            .collect();
+        let mut input = format!("seeds: {}\n", seeds.join(" "));
+
+        // Lay the ranges out one after the other, in a random order and
+        // with random gaps.
+        let layout = |rng: &mut Rng, lens: &[u64]| {
+            let mut order: Vec<usize> = (0..lens.len()).collect();
+            rng.shuffle(&mut order);
+            let mut starts = vec![0; lens.len()];
+            let mut at = 0;
+            for i in order {
+                at += rng.range(0..span / 2);
+                starts[i] = at;
+                at += lens[i];
+            }
+            starts
+        };
+        for name in NAMES {
+            let lens: Vec<u64> = (0..size).map(|_| rng.range(1..span / 2)).collect();
+            let sources = layout(rng, &lens);
+            let dests = layout(rng, &lens);
+            input.push_str(&format!("\n{} map:\n", name));
+            for i in 0..lens.len() {
+                input.push_str(&format!("{} {} {}\n", dests[i], sources[i], lens[i]));
+            }
+        }
+        input
+    }
+}
+
+impl Reference for Day5 {
+    fn naive_part_one(almanac: &Self::Model) -> Option<u64> {
+        almanac
+            .seeds
+            .iter()
+            .map(|&s| naive_location(almanac, s))
+            .min()
+    }
 
-    lines.by_ref().next();
-    while let Some(_) = lines.by_ref().next() {
-        ranges = apply_map(ranges, lines.by_ref());
+    // Follow every seed of every range on its own.
+    fn naive_part_two(almanac: &Self::Model) -> Option<u64> {
+        let pairs: Vec<_> = almanac.seeds.chunks_exact(2).collect();
+        if pairs.iter().map(|p| p[1]).sum::<u64>() > 1_000_000 {
+            return None;
+        }
+        pairs
+            .iter()
+            .flat_map(|p| p[0]..p[0] + p[1])
+            .map(|s| naive_location(almanac, s))
+            .min()
     }
-    ranges.iter().map(|r| r.start).min().unwrap()
 }
 
-pub fn part_two(input: &str) -> u64 {
-    let mut lines = input.lines();
+fn map_key(map: &Map, key: u64) -> u64 {
+    match map.iter().find(|(range, _)| range.contains(&key)) {
+        Some((_, offset)) => (key as i128 + offset) as u64,
+        None => key,
+    }
+}
 
-    let (_, seeds) = lines
-        .by_ref()
-        .find(|l| !l.trim().is_empty())
-        .unwrap()
-        .split_once(':')
-        .unwrap();
+fn naive_location(almanac: &Almanac, seed: u64) -> u64 {
+    almanac.maps.iter().fold(seed, |key, map| map_key(map, key))
+}
 
-    let seeds: Vec<u64> = seeds
-        .split_whitespace()
-        .map(|n| n.parse::<u64>().unwrap())
-        .collect();
+pub fn part_one(almanac: &Almanac) -> Result<u64, PuzzleError> {
+    let ranges: Vec<Range> = almanac.seeds.iter().map(|s| *s..(*s + 1)).collect();
+    almanac.lowest_location(ranges)
+}
 
-    let mut ranges: Vec<Range> = (0..seeds.len())
+pub fn part_two(almanac: &Almanac) -> Result<u64, PuzzleError> {
+    let seeds = &almanac.seeds;
+    if !seeds.len().is_multiple_of(2) {
+        return Err(PuzzleError::new("seeds do not pair up into ranges"));
+    }
+    let ranges = (0..seeds.len())
         .step_by(2)
         .map(|i| {
             let start = seeds[i];
             let delta = seeds[i + 1];
-            start..start + delta + 1
+            match start.checked_add(delta) {
+                Some(end) => Ok(start..end),
+                None => Err(PuzzleError::new(format!(
+                    "seed range {} {} runs past the largest number",
+                    start, delta
+                ))),
+            }
         })
-        .collect();
-
-    lines.by_ref().next();
-    while let Some(_) = lines.by_ref().next() {
-        ranges = apply_map(ranges, lines.by_ref());
-    }
-    ranges.iter().map(|r| r.start).min().unwrap()
+        .collect::<Result<Vec<Range>, _>>()?;
+    almanac.lowest_location(ranges)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{part_one, part_two};
+    use super::{part_one, part_two, Almanac, Day5};
+    use crate::solution::{Lint, Reference};
 
     #[test]
     fn test_puzzle_example() {
@@ -204,7 +442,48 @@ humidity-to-location map:
 60 56 37
 56 93 4
 ";
-        assert_eq!(35, part_one(input));
-        assert_eq!(46, part_two(input));
+        let almanac = Almanac::new(input).unwrap();
+        assert_eq!(Ok(35), part_one(&almanac));
+        assert_eq!(Ok(46), part_two(&almanac));
+    }
+
+    #[test]
+    fn test_seed_range_end() {
+        // Seed 80 is the first one past the range 79..80 and would land
+        // on location 0.
+        let almanac = Almanac::new("seeds: 79 1\n\nseed-to-soil map:\n0 80 1\n").unwrap();
+        assert_eq!(Ok(79), part_two(&almanac));
+    }
+
+    #[test]
+    fn test_large_offset() {
+        // The offset from 1 to 2^63 does not fit in an i64.
+        let almanac =
+            Almanac::new("seeds: 1 1\n\nseed-to-soil map:\n9223372036854775808 1 1\n").unwrap();
+        assert_eq!(Ok(9223372036854775808), part_one(&almanac));
+        assert_eq!(Ok(9223372036854775808), part_two(&almanac));
+        assert_eq!(Some(9223372036854775808), Day5::naive_part_one(&almanac));
+    }
+
+    #[test]
+    fn test_lint_overlaps() {
+        let input = "seeds: 1 2\n\na map:\n0 10 5\n0 20 5\n0 0 50\n0 14 1\n\nb map:\n0 14 1\n";
+        let mut lines: Vec<_> = Day5::lint(input)
+            .iter()
+            .map(|e| e.span.as_ref().unwrap().line)
+            .collect();
+        lines.sort();
+        assert_eq!(vec![4, 5, 7], lines);
+    }
+
+    #[test]
+    fn test_range_overflow() {
+        let input = "seeds: 1 2\n\na map:\n0 18446744073709551610 10\n";
+        let span = Almanac::new(input).err().unwrap().span.unwrap();
+        assert_eq!(4, span.line);
+        assert_eq!(1, Day5::lint(input).len());
+
+        let almanac = Almanac::new("seeds: 18446744073709551610 10\n\na map:\n0 0 1\n").unwrap();
+        assert!(part_two(&almanac).is_err());
     }
 }
@@ -1,37 +1,212 @@
-pub fn part_one(input: &str) -> usize {
-    let mut lines = input.lines().filter(|l| !l.trim().is_empty()).map(|l| {
-        let (_, nums) = l.split_once(':').unwrap();
-        nums.split_whitespace()
-            .map(|num| num.parse::<u64>().unwrap())
-            .collect::<Vec<_>>()
-    });
-
-    let time = lines.next().unwrap();
-    let distance = lines.next().unwrap();
-
-    time.iter()
-        .zip(distance.iter())
-        .map(|(t, d)| (0..*t).filter(|x| (t - x) * x > *d).count())
-        .product()
+use crate::error::PuzzleError;
+use crate::parse;
+use crate::rng::Rng;
+use crate::solution::{Explain, Generator, Lint, Part, Reference, Solution, Step};
+
+pub struct Races {
+    times: Vec<u64>,
+    distances: Vec<u64>,
+}
+
+impl Races {
+    pub fn new(input: &str) -> Result<Self, PuzzleError> {
+        let mut lines = parse::lines(input).map(|l| {
+            let (_, nums) = l.split_once(l.text, ':')?;
+            Ok((l, l.numbers::<u64>(nums)?))
+        });
+
+        let mut next = |what: &str| {
+            lines
+                .next()
+                .unwrap_or_else(|| Err(PuzzleError::new(format!("missing {} line", what))))
+        };
+        let (_, times) = next("time")?;
+        let (line, distances) = next("distance")?;
+
+        if times.len() != distances.len() {
+            return Err(line.error(line.text, "expected a distance for every race"));
+        }
+
+        Ok(Races { times, distances })
+    }
+
+    pub fn times(&self) -> &[u64] {
+        &self.times
+    }
+
+    pub fn distances(&self) -> &[u64] {
+        &self.distances
+    }
+}
+
+// The numbers on a line read as a single number once the spaces
+// between them are dropped.
+fn kern(nums: &[u64]) -> Result<u64, PuzzleError> {
+    let kerned = nums.iter().map(|num| num.to_string()).collect::<String>();
+    kerned
+        .parse::<u64>()
+        .map_err(|_| PuzzleError::new(format!("{} is too long for a race", kerned)))
 }
 
-pub fn part_two(input: &str) -> u64 {
-    let mut lines = input.lines().filter(|l| !l.trim().is_empty()).map(|l| {
-        let (_, nums) = l.split_once(':').unwrap();
-        let nums = nums
-            .split_whitespace()
-            .flat_map(|num| num.chars())
-            .collect::<String>();
-        nums.parse::<u64>().unwrap()
-    });
+pub struct Day6;
+
+impl Solution for Day6 {
+    const YEAR: u32 = 2023;
+    const DAY: u32 = 6;
+    const TITLE: &'static str = "Wait For It";
 
-    let time = lines.next().unwrap();
-    let distance = lines.next().unwrap();
+    type Model = Races;
+    type PartOne = usize;
+    type PartTwo = u64;
 
+    fn parse(input: &str) -> Result<Self::Model, PuzzleError> {
+        Races::new(input)
+    }
+
+    fn part_one(races: &Self::Model) -> Result<usize, PuzzleError> {
+        Ok(part_one(races))
+    }
+
+    fn part_two(races: &Self::Model) -> Result<u64, PuzzleError> {
+        part_two(races)
+    }
+}
+
+impl Lint for Day6 {
+    // Both lines, their labels and every number on them are checked on
+    // their own, and the race counts are compared when both lines are
+    // there.
+    fn lint(input: &str) -> Vec<PuzzleError> {
+        let mut errors = vec![];
+        let mut lines = parse::lines(input);
+        let mut counts = vec![];
+        for label in ["Time", "Distance"] {
+            let Some(line) = lines.next() else {
+                errors.push(PuzzleError::new(format!(
+                    "missing {} line",
+                    label.to_lowercase()
+                )));
+                continue;
+            };
+            let (name, nums) = match line.split_once(line.text, ':') {
+                Ok(parts) => parts,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+            if name.trim() != label {
+                errors.push(line.error(name, format!("expected '{}'", label)));
+            }
+            let nums: Vec<&str> = nums.split_whitespace().collect();
+            errors.extend(nums.iter().filter_map(|num| line.number::<u64>(num).err()));
+            counts.push((line, nums.len()));
+        }
+        if let [(_, times), (line, distances)] = counts[..] {
+            if times != distances {
+                errors.push(line.error(line.text, "expected a distance for every race"));
+            }
+        }
+        errors
+    }
+}
+
+impl Explain for Day6 {
+    // The shortest and longest holds that beat the record of each race,
+    // as the binary search finds them; part two has the one kerned race.
+    fn explain(races: &Self::Model, part: Part) -> Vec<Step> {
+        let races = match part {
+            Part::One => races
+                .times
+                .iter()
+                .copied()
+                .zip(races.distances.iter().copied())
+                .collect(),
+            Part::Two => match (kern(&races.times), kern(&races.distances)) {
+                (Ok(time), Ok(distance)) => vec![(time, distance)],
+                (Err(e), _) | (_, Err(e)) => return vec![Step::new().with("error", e.message)],
+            },
+        };
+        races
+            .into_iter()
+            .map(|(time, distance)| {
+                let step = Step::new().with("time", time).with("distance", distance);
+                match holds(time, distance) {
+                    Some((shortest, longest)) => step
+                        .with("shortest", shortest)
+                        .with("longest", longest)
+                        .with("ways", longest - shortest + 1),
+                    None => step.with("ways", 0),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Generator for Day6 {
+    // Part two reads the races as one, joining their digits, so there
+    // are at most four races to keep that race in range.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let races = size.clamp(1, 4);
+        loop {
+            let times: Vec<u64> = (0..races).map(|_| rng.range(10..100)).collect();
+            let distances: Vec<u64> = times
+                .iter()
+                .map(|t| rng.range(1..((t / 2) * (t - t / 2)).min(1000)))
+                .collect();
+            let (Ok(time), Ok(distance)) = (kern(&times), kern(&distances)) else {
+                continue;
+            };
+            if (time / 2) * (time - time / 2) <= distance {
+                continue;
+            }
+            let show = |nums: &[u64]| nums.iter().map(|n| format!("{:>5}", n)).collect::<String>();
+            return format!("Time:    {}\nDistance:{}\n", show(&times), show(&distances));
+        }
+    }
+}
+
+impl Reference for Day6 {
+    // Try every time the button could be held.
+    fn naive_part_two(races: &Self::Model) -> Option<u64> {
+        let time = kern(&races.times).ok()?;
+        let distance = kern(&races.distances).ok()?;
+        if time > 1_000_000 {
+            return None;
+        }
+        Some((0..=time).filter(|x| (time - x) * x > distance).count() as u64)
+    }
+}
+
+// Whether holding the button for `hold` of the race's `time` goes
+// further than `distance`, without overflowing on long races.
+fn beats(time: u64, hold: u64, distance: u64) -> bool {
+    (time - hold) as u128 * hold as u128 > distance as u128
+}
+
+pub fn part_one(races: &Races) -> usize {
+    races
+        .times
+        .iter()
+        .zip(races.distances.iter())
+        .map(|(&t, &d)| (0..t).filter(|&x| beats(t, x, d)).count())
+        .product()
+}
+
+// The shortest and longest holds that beat the record, if any do.
+fn holds(time: u64, distance: u64) -> Option<(u64, u64)> {
+    // Holding for half the race goes furthest; when even that does not
+    // beat the record, nothing does.
+    if !beats(time, time / 2, distance) {
+        return None;
+    }
+
+    // The shortest hold that beats the record, between `lo`, which does
+    // not, and `up`, which does.
     let binary_search = |mut lo: u64, mut up: u64| {
-        while lo < up - 1 {
+        while lo + 1 < up {
             let mid = lo + (up - lo) / 2;
-            if (time - mid) * mid > distance {
+            if beats(time, mid, distance) {
                 up = mid;
             } else {
                 lo = mid;
@@ -39,19 +214,21 @@ pub fn part_two(input: &str) -> u64 {
         }
         up
     };
-    let lower = binary_search(0, time / 2);
-    let ways = (time / 2 - lower + 1) * 2;
+    let shortest = binary_search(0, time / 2);
+    // The distance is symmetric in holding and moving.
+    Some((shortest, time - shortest))
+}
 
-    if time % 2 == 0 {
-        ways - 1
-    } else {
-        ways
-    }
+pub fn part_two(races: &Races) -> Result<u64, PuzzleError> {
+    let time = kern(&races.times)?;
+    let distance = kern(&races.distances)?;
+    Ok(holds(time, distance).map_or(0, |(shortest, longest)| longest - shortest + 1))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{part_one, part_two};
+    use super::{part_one, part_two, Day6, Races};
+    use crate::solution::{Explain, Lint, Part};
 
     #[test]
     fn test_puzzle_example() {
@@ -59,7 +236,72 @@ mod tests {
 Time:      7  15   30
 Distance:  9  40  200
 ";
-        assert_eq!(288, part_one(input));
-        assert_eq!(71503, part_two(input));
+        let races = Races::new(input).unwrap();
+        assert_eq!(288, part_one(&races));
+        assert_eq!(Ok(71503), part_two(&races));
+    }
+
+    #[test]
+    fn test_explain() {
+        let races = Races::new(include_str!("../examples/day6/example.txt")).unwrap();
+        let steps = Day6::explain(&races, Part::One);
+        assert_eq!(
+            "time=7 distance=9 shortest=2 longest=5 ways=4",
+            steps[0].to_string()
+        );
+        assert_eq!(
+            "time=30 distance=200 shortest=11 longest=19 ways=9",
+            steps[2].to_string()
+        );
+        assert_eq!(
+            vec!["time=71530 distance=940200 shortest=14 longest=71516 ways=71503"],
+            Day6::explain(&races, Part::Two)
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+        );
+        let races = Races::new("Time: 4\nDistance: 4\n").unwrap();
+        assert_eq!(
+            "time=4 distance=4 ways=0",
+            Day6::explain(&races, Part::One)[0].to_string()
+        );
+    }
+
+    #[test]
+    fn test_unwinnable() {
+        for input in [
+            "Time: 1\nDistance: 0\n",
+            "Time: 0\nDistance: 0\n",
+            "Time: 4\nDistance: 4\n",
+        ] {
+            let races = Races::new(input).unwrap();
+            assert_eq!(0, part_one(&races));
+            assert_eq!(Ok(0), part_two(&races));
+        }
+        let races = Races::new("Time: 2\nDistance: 0\n").unwrap();
+        assert_eq!(Ok(1), part_two(&races));
+    }
+
+    #[test]
+    fn test_lint_every_problem() {
+        let messages = |input| -> Vec<String> {
+            Day6::lint(input)
+                .iter()
+                .map(|e| e.message.clone())
+                .collect()
+        };
+        assert_eq!(
+            vec![
+                "expected a number",
+                "expected a number",
+                "expected a distance for every race"
+            ],
+            messages("Time: 7 x 30\nDistance: 9 y\n")
+        );
+        assert_eq!(
+            vec!["missing time line", "missing distance line"],
+            messages("")
+        );
+        assert!(messages(include_str!("../examples/day6/example.txt")).is_empty());
     }
 }
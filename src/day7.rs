@@ -1,9 +1,15 @@
 use std::cmp::Ord;
+use std::fmt;
 use std::str::FromStr;
 use std::{cmp::Ordering, collections::HashMap};
 
+use crate::error::PuzzleError;
+use crate::parse::{self, Line};
+use crate::rng::Rng;
+use crate::solution::{Explain, Generator, Lint, Part, Query, Solution, Step};
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum Card {
+pub enum Card {
     C1,
     C2,
     C3,
@@ -20,18 +26,13 @@ enum Card {
     A,
 }
 
-#[derive(Debug)]
-enum HandParseErr {
-    Unexpected(char),
-    Eof,
-}
-
 impl FromStr for Card {
-    type Err = HandParseErr;
+    type Err = PuzzleError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = Line::detached(s);
         match s.chars().next() {
-            None => Err(HandParseErr::Eof),
+            None => Err(line.error(s, "expected a card")),
             Some(c) => {
                 let card = match c {
                     '1' => Card::C1,
@@ -49,7 +50,7 @@ impl FromStr for Card {
                     'K' => Card::K,
                     'A' => Card::A,
                     _ => {
-                        return Err(HandParseErr::Unexpected(c));
+                        return Err(line.error(&s[..c.len_utf8()], "unexpected card"));
                     }
                 };
                 Ok(card)
@@ -58,8 +59,30 @@ impl FromStr for Card {
     }
 }
 
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let c = match self {
+            Card::C1 => '1',
+            Card::C2 => '2',
+            Card::C3 => '3',
+            Card::C4 => '4',
+            Card::C5 => '5',
+            Card::C6 => '6',
+            Card::C7 => '7',
+            Card::C8 => '8',
+            Card::C9 => '9',
+            Card::T => 'T',
+            Card::J => 'J',
+            Card::Q => 'Q',
+            Card::K => 'K',
+            Card::A => 'A',
+        };
+        write!(f, "{}", c)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-enum Kind {
+pub enum Kind {
     HighCard,
     OnePair,
     TwoPair,
@@ -70,11 +93,15 @@ enum Kind {
 }
 
 #[derive(Debug)]
-struct Hand {
+pub struct Hand {
     cards: Vec<Card>,
 }
 
 impl Hand {
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
     pub fn kind(&self) -> Kind {
         let mut card_freq = HashMap::new();
 
@@ -104,6 +131,12 @@ impl Hand {
     }
 }
 
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.cards.iter().try_for_each(|card| write!(f, "{}", card))
+    }
+}
+
 impl PartialEq for Hand {
     fn eq(&self, other: &Hand) -> bool {
         self.cmp(other) == Ordering::Equal
@@ -129,28 +162,141 @@ impl PartialOrd for Hand {
 }
 
 impl FromStr for Hand {
-    type Err = HandParseErr;
+    type Err = PuzzleError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = Line::detached(s);
         let mut cards = vec![];
-        for i in 0..s.len() {
-            cards.push(s[i..].parse::<Card>()?);
+        for (i, _) in s.char_indices() {
+            let card = s[i..]
+                .parse::<Card>()
+                .map_err(|e| line.within(&s[i..], e))?;
+            cards.push(card);
+        }
+        if cards.len() != 5 {
+            return Err(line.error(s, "expected five cards"));
         }
         Ok(Hand { cards })
     }
 }
 
-pub fn part_one(input: &str) -> usize {
-    let mut hands: Vec<_> = input
-        .lines()
-        .filter(|l| !l.trim().is_empty())
+pub struct Day7;
+
+impl Solution for Day7 {
+    const YEAR: u32 = 2023;
+    const DAY: u32 = 7;
+    const TITLE: &'static str = "Camel Cards";
+
+    type Model = Vec<(Hand, usize)>;
+    type PartOne = usize;
+    type PartTwo = u64;
+
+    fn parse(input: &str) -> Result<Self::Model, PuzzleError> {
+        parse(input)
+    }
+
+    fn part_one(hands: &Self::Model) -> Result<usize, PuzzleError> {
+        Ok(part_one(hands))
+    }
+
+    fn part_two(hands: &Self::Model) -> Result<u64, PuzzleError> {
+        Ok(part_two(hands))
+    }
+}
+
+impl Lint for Day7 {
+    // Every card of every hand is checked, as well as the hand's size.
+    fn lint(input: &str) -> Vec<PuzzleError> {
+        let mut errors = vec![];
+        for line in parse::lines(input) {
+            let (hand, bid) = match line.split_once(line.text.trim(), ' ') {
+                Ok(parts) => parts,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+            for (i, _) in hand.char_indices() {
+                if let Err(e) = hand[i..].parse::<Card>() {
+                    errors.push(line.within(&hand[i..], e));
+                }
+            }
+            if hand.chars().count() != 5 {
+                errors.push(line.error(hand, "expected five cards"));
+            }
+            errors.extend(line.number::<usize>(bid.trim()).err());
+        }
+        errors
+    }
+}
+
+impl Query for Day7 {
+    const QUERIES: &'static [(&'static str, &'static str)] = &[("hand", "<cards>")];
+
+    // The kind of a hand and where it ranks among those of the input,
+    // with its bid if it is one of them.
+    fn query(hands: &Self::Model, _name: &str, arg: &str) -> Result<Step, String> {
+        let hand: Hand = arg.parse().map_err(|e: PuzzleError| e.to_string())?;
+        let rank = 1 + hands.iter().filter(|(h, _)| *h < hand).count();
+        let step = Step::new()
+            .with("hand", arg)
+            .with("kind", format!("{:?}", hand.kind()))
+            .with("rank", rank)
+            .with("of", hands.len());
+        match hands.iter().find(|(h, _)| h.cards == hand.cards) {
+            Some((_, bid)) => Ok(step.with("bid", bid)),
+            None => Ok(step),
+        }
+    }
+}
+
+impl Explain for Day7 {
+    // The kind and rank of every hand, in input order, and what its bid
+    // wins. Part two is not solved yet.
+    fn explain(hands: &Self::Model, part: Part) -> Vec<Step> {
+        if part == Part::Two {
+            return vec![Step::new().with("error", "part two is not solved yet")];
+        }
+        hands
+            .iter()
+            .map(|(hand, bid)| {
+                let rank = 1 + hands.iter().filter(|(h, _)| h < hand).count();
+                Step::new()
+                    .with("hand", hand)
+                    .with("kind", format!("{:?}", hand.kind()))
+                    .with("rank", rank)
+                    .with("bid", bid)
+                    .with("wins", rank * bid)
+            })
+            .collect()
+    }
+}
+
+impl Generator for Day7 {
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        const CARDS: &[u8] = b"23456789TJQKA";
+        let mut input = String::new();
+        for _ in 0..size {
+            let hand: String = (0..5).map(|_| char::from(*rng.pick(CARDS))).collect();
+            input.push_str(&format!("{} {}\n", hand, rng.range(1..1001)));
+        }
+        input
+    }
+}
+
+pub fn parse(input: &str) -> Result<Vec<(Hand, usize)>, PuzzleError> {
+    parse::lines(input)
         .map(|l| {
-            let (hand, bid) = l.split_once(' ').unwrap();
-            let hand = hand.parse::<Hand>().unwrap();
-            let bid = bid.parse::<usize>().unwrap();
-            (hand, bid)
+            let (hand, bid) = l.split_once(l.text.trim(), ' ')?;
+            let hand = hand.parse::<Hand>().map_err(|e| l.within(hand, e))?;
+            let bid = l.number::<usize>(bid.trim())?;
+            Ok((hand, bid))
         })
-        .collect();
+        .collect()
+}
+
+pub fn part_one(hands: &[(Hand, usize)]) -> usize {
+    let mut hands: Vec<_> = hands.iter().collect();
 
     hands.sort_by(|lh, rh| lh.0.cmp(&rh.0));
 
@@ -160,13 +306,14 @@ pub fn part_one(input: &str) -> usize {
         .fold(0, |sum, (i, h)| sum + (h.1) * (i + 1))
 }
 
-pub fn part_two(input: &str) -> u64 {
+pub fn part_two(_hands: &[(Hand, usize)]) -> u64 {
     todo!()
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{part_one, part_two};
+    use super::{parse, part_one, Day7};
+    use crate::solution::{Explain, Part};
 
     #[test]
     fn test_puzzle_example() {
@@ -177,7 +324,22 @@ KK677 28
 KTJJT 220
 QQQJA 483
 ";
-        assert_eq!(6440, part_one(input));
-        //assert_eq!(71503, part_two(input));
+        let hands = parse(input).unwrap();
+        assert_eq!(6440, part_one(&hands));
+        //assert_eq!(71503, part_two(&hands));
+    }
+
+    #[test]
+    fn test_explain() {
+        let hands = parse(include_str!("../examples/day7/example.txt")).unwrap();
+        let steps = Day7::explain(&hands, Part::One);
+        assert_eq!(
+            "hand=32T3K kind=OnePair rank=1 bid=765 wins=765",
+            steps[0].to_string()
+        );
+        assert_eq!(
+            "hand=QQQJA kind=ThreeOf rank=5 bid=483 wins=2415",
+            steps[4].to_string()
+        );
     }
 }
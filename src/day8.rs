@@ -1,49 +1,84 @@
 use num::integer::lcm;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use crate::error::PuzzleError;
+use crate::parse;
+use crate::rng::Rng;
+use crate::solution::{Explain, Generator, Lint, Part, Query, Reference, Solution, Step};
 
 type Map = HashMap<String, (String, String)>;
 
-struct Network {
+pub struct Network {
     map: Map,
     seq: String,
 }
 
 impl Network {
-    fn new(input: &str) -> Self {
-        let mut lines = input.lines().filter(|l| !l.trim().is_empty());
-        let seq = lines.next().unwrap().to_string();
+    pub fn new(input: &str) -> Result<Self, PuzzleError> {
+        let mut lines = parse::lines(input);
+        let seq = lines
+            .next()
+            .ok_or_else(|| PuzzleError::new("missing instructions"))?;
+        if let Some((i, c)) = seq
+            .text
+            .char_indices()
+            .find(|(_, c)| *c != 'L' && *c != 'R')
+        {
+            return Err(seq.error(&seq.text[i..i + c.len_utf8()], "expected 'L' or 'R'"));
+        }
 
         let mut map = HashMap::new();
+        let mut adjacent = vec![];
 
         for line in lines {
             let (node, adj) = line
-                .split_once('=')
-                .map(|(l, r)| (l.trim(), r.trim()))
-                .unwrap();
-
-            let (left, right) = adj
-                .split_once(',')
-                .map(|(l, r)| {
-                    (
-                        l.strip_prefix('(').unwrap().trim(),
-                        r.strip_suffix(')').unwrap().trim(),
-                    )
-                })
-                .unwrap();
+                .split_once(line.text, '=')
+                .map(|(l, r)| (l.trim(), r.trim()))?;
+
+            let (left, right) = line.split_once(adj, ',')?;
+            let left = left
+                .strip_prefix('(')
+                .ok_or_else(|| line.error(left, "expected '('"))?
+                .trim();
+            let right = right
+                .strip_suffix(')')
+                .ok_or_else(|| line.error(right, "expected ')'"))?
+                .trim();
 
             map.insert(node.to_string(), (left.to_string(), right.to_string()));
+            adjacent.push((line, left));
+            adjacent.push((line, right));
         }
 
-        Network { map, seq }
+        if let Some((line, node)) = adjacent.iter().find(|(_, n)| !map.contains_key(*n)) {
+            return Err(line.error(node, "undefined node"));
+        }
+
+        Ok(Network {
+            map,
+            seq: seq.text.trim().to_string(),
+        })
+    }
+
+    pub fn seq(&self) -> &str {
+        &self.seq
+    }
+
+    pub fn neighbors(&self, node: &str) -> Option<(&str, &str)> {
+        self.map.get(node).map(|(l, r)| (l.as_str(), r.as_str()))
     }
 
-    fn iter_from<'a>(&'a self, start: &'a str) -> NetworkIter {
+    pub fn nodes(&self) -> impl Iterator<Item = &str> {
+        self.map.keys().map(|k| k.as_str())
+    }
+
+    pub fn iter_from<'a>(&'a self, start: &'a str) -> NetworkIter<'a> {
         NetworkIter::new(self, start)
     }
 }
 
-struct NetworkIter<'a> {
+pub struct NetworkIter<'a> {
     network: &'a Network,
     curr: &'a str,
     steps: usize,
@@ -60,7 +95,7 @@ impl<'a> NetworkIter<'a> {
         }
     }
 
-    fn steps(&self) -> usize {
+    pub fn steps(&self) -> usize {
         self.steps
     }
 }
@@ -78,20 +113,221 @@ impl<'a> Iterator for NetworkIter<'a> {
     }
 }
 
-pub fn part_one(input: &str) -> u64 {
-    let nw = Network::new(input);
+pub struct Day8;
+
+impl Solution for Day8 {
+    const YEAR: u32 = 2023;
+    const DAY: u32 = 8;
+    const TITLE: &'static str = "Haunted Wasteland";
+
+    type Model = Network;
+    type PartOne = u64;
+    type PartTwo = u64;
+
+    fn parse(input: &str) -> Result<Self::Model, PuzzleError> {
+        Network::new(input)
+    }
+
+    fn part_one(nw: &Self::Model) -> Result<u64, PuzzleError> {
+        part_one(nw)
+    }
+
+    fn part_two(nw: &Self::Model) -> Result<u64, PuzzleError> {
+        Ok(part_two(nw))
+    }
+}
+
+impl Lint for Day8 {
+    // Reports every bad instruction, every node defined twice and every
+    // reference to a node that is not defined.
+    fn lint(input: &str) -> Vec<PuzzleError> {
+        let mut lines = parse::lines(input);
+        let Some(seq) = lines.next() else {
+            return vec![PuzzleError::new("missing instructions")];
+        };
+        let mut errors: Vec<_> = seq
+            .text
+            .char_indices()
+            .filter(|(_, c)| *c != 'L' && *c != 'R')
+            .map(|(i, c)| seq.error(&seq.text[i..i + c.len_utf8()], "expected 'L' or 'R'"))
+            .collect();
+
+        let mut defined = HashMap::new();
+        let mut referenced = vec![];
+        for line in lines {
+            let Some((node, adj)) = line.text.split_once('=') else {
+                errors.push(line.error(line.text, "expected '='"));
+                continue;
+            };
+            let node = node.trim();
+            if let Some(first) = defined.insert(node, line.no) {
+                errors.push(line.error(node, format!("node already defined on line {}", first)));
+            }
+            let adj = adj.trim();
+            let Some(pair) = adj.strip_prefix('(').and_then(|a| a.strip_suffix(')')) else {
+                errors.push(line.error(adj, "expected '(<left>, <right>)'"));
+                continue;
+            };
+            match pair.split_once(',') {
+                Some((left, right)) => {
+                    referenced.extend([(line, left.trim()), (line, right.trim())])
+                }
+                None => errors.push(line.error(pair, "expected ','")),
+            }
+        }
+        errors.extend(
+            referenced
+                .iter()
+                .filter(|(_, node)| !defined.contains_key(node))
+                .map(|(line, node)| line.error(node, "undefined node")),
+        );
+        errors
+    }
+}
+
+impl Explain for Day8 {
+    // Where and after how many steps each walker first lands on its
+    // goal, and for part two the least common multiple of those.
+    fn explain(nw: &Self::Model, part: Part) -> Vec<Step> {
+        let (mut starts, goal): (Vec<&str>, fn(&str) -> bool) = match part {
+            Part::One => (vec!["AAA"], |n| n == "ZZZ"),
+            Part::Two => (nw.nodes().filter(|n| n.ends_with('A')).collect(), |n| {
+                n.ends_with('Z')
+            }),
+        };
+        starts.sort();
+        let mut steps = vec![];
+        let mut all = 1;
+        for start in starts {
+            let step = Step::new().with("walker", start);
+            if nw.neighbors(start).is_none() {
+                steps.push(step.with("error", "no such node"));
+                continue;
+            }
+            // Give up where part one would loop forever.
+            let limit = nw.map.len() * nw.seq.len() + 1;
+            let mut walker = nw.iter_from(start);
+            match walker.by_ref().take(limit).find(|n| goal(n)) {
+                Some(hit) => {
+                    all = lcm(all, walker.steps());
+                    steps.push(step.with("first", hit).with("steps", walker.steps()));
+                }
+                None => steps.push(step.with("first", "never")),
+            }
+        }
+        if part == Part::Two {
+            steps.push(Step::new().with("lcm", all));
+        }
+        steps
+    }
+}
+
+impl Query for Day8 {
+    const QUERIES: &'static [(&'static str, &'static str)] = &[("node", "<name>")];
+
+    fn query(nw: &Self::Model, _name: &str, arg: &str) -> Result<Step, String> {
+        let (left, right) = nw
+            .neighbors(arg)
+            .ok_or_else(|| format!("no node {}", arg))?;
+        Ok(Step::new()
+            .with("node", arg)
+            .with("left", left)
+            .with("right", right))
+    }
+}
+
+impl Generator for Day8 {
+    // A network of about `size` nodes in which every walker, AAA first,
+    // goes round a loop of layers of two nodes each. Left and right
+    // lead to the same next layer, so a walker steps on its Z node
+    // every lap whatever the instructions, as part two assumes.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        const LETTERS: &[u8] = b"BCDEFGHIJKLMNOPQRSTUVWXY";
+        let size = size.clamp(4, 10_000);
+        let mut used: HashSet<String> = ["AAA", "ZZZ"].map(String::from).into();
+        let mut name = |rng: &mut Rng, last: Option<u8>| loop {
+            let mut name: String = (0..2)
+                .map(|_| char::from(b'A' + rng.index(26) as u8))
+                .collect();
+            name.push(char::from(last.unwrap_or_else(|| *rng.pick(LETTERS))));
+            if used.insert(name.clone()) {
+                return name;
+            }
+        };
+
+        let walkers = (1 + size / 10).min(6);
+        let mut nodes = vec![];
+        for walker in 0..walkers {
+            let (start, end) = if walker == 0 {
+                ("AAA".to_string(), "ZZZ".to_string())
+            } else {
+                (name(rng, Some(b'A')), name(rng, Some(b'Z')))
+            };
+            let layers: Vec<[String; 2]> = (0..rng.index(size / walkers / 2) + 1)
+                .map(|_| [name(rng, None), name(rng, None)])
+                .collect();
+            let mut link = |rng: &mut Rng, from: &String, to: &[String; 2]| {
+                let (left, right) = if rng.chance(0.5) { (0, 1) } else { (1, 0) };
+                nodes.push(format!("{} = ({}, {})", from, to[left], to[right]));
+            };
+            link(rng, &start, &layers[0]);
+            link(rng, &end, &layers[0]);
+            for (i, layer) in layers.iter().enumerate() {
+                let next = layers
+                    .get(i + 1)
+                    .cloned()
+                    .unwrap_or([end.clone(), end.clone()]);
+                for node in layer {
+                    link(rng, node, &next);
+                }
+            }
+        }
+        rng.shuffle(&mut nodes);
+
+        let seq: String = (0..rng.index(size / 4 + 1) + 1)
+            .map(|_| if rng.chance(0.5) { 'L' } else { 'R' })
+            .collect();
+        format!("{}\n\n{}\n", seq, nodes.join("\n"))
+    }
+}
+
+impl Reference for Day8 {
+    // Walk all the ghosts in step until they all stand on a Z node.
+    fn naive_part_two(nw: &Self::Model) -> Option<u64> {
+        let mut walkers: Vec<_> = nw
+            .map
+            .keys()
+            .filter(|k| k.ends_with('A'))
+            .map(|k| nw.iter_from(k))
+            .collect();
+        for steps in 1..=1_000_000 {
+            // Every walker takes its step, even once one is off a Z node.
+            let mut at_z = true;
+            for walker in &mut walkers {
+                at_z &= walker.next().is_some_and(|n| n.ends_with('Z'));
+            }
+            if at_z {
+                return Some(steps);
+            }
+        }
+        None
+    }
+}
+
+pub fn part_one(nw: &Network) -> Result<u64, PuzzleError> {
+    if !nw.map.contains_key("AAA") {
+        return Err(PuzzleError::new("no node AAA to start from"));
+    }
     let mut iter = nw.iter_from("AAA");
     for node in &mut iter {
         if node == "ZZZ" {
-            return iter.steps() as u64;
+            return Ok(iter.steps() as u64);
         }
     }
-    0
+    unreachable!("the instructions repeat forever")
 }
 
-pub fn part_two(input: &str) -> u64 {
-    let nw = Network::new(input);
-
+pub fn part_two(nw: &Network) -> u64 {
     let mut walkers: Vec<_> = nw
         .map
         .keys()
@@ -113,7 +349,8 @@ pub fn part_two(input: &str) -> u64 {
 
 #[cfg(test)]
 mod tests {
-    use super::{part_one, part_two};
+    use super::{part_one, part_two, Day8, Network};
+    use crate::solution::Lint;
 
     #[test]
     fn test_puzzle_example_1() {
@@ -128,7 +365,7 @@ EEE = (EEE, EEE)
 GGG = (GGG, GGG)
 ZZZ = (ZZZ, ZZZ)
 ";
-        assert_eq!(2, part_one(input));
+        assert_eq!(Ok(2), part_one(&Network::new(input).unwrap()));
     }
 
     #[test]
@@ -140,7 +377,7 @@ AAA = (BBB, BBB)
 BBB = (AAA, ZZZ)
 ZZZ = (ZZZ, ZZZ)
 ";
-        assert_eq!(6, part_one(input));
+        assert_eq!(Ok(6), part_one(&Network::new(input).unwrap()));
     }
 
     #[test]
@@ -157,6 +394,25 @@ LR
 22Z = (22B, 22B)
 XXX = (XXX, XXX)
 ";
-        assert_eq!(6, part_two(input));
+        assert_eq!(6, part_two(&Network::new(input).unwrap()));
+    }
+
+    #[test]
+    fn test_bad_instruction() {
+        let err = Network::new("LRé\n\nAAA = (AAA, AAA)\n").err().unwrap();
+        let span = err.span.unwrap();
+        assert_eq!((1, 3, "é"), (span.line, span.column, span.snippet.as_str()));
+    }
+
+    #[test]
+    fn test_lint_undefined_nodes() {
+        let input = "LR\n\nAAA = (BBB, CCC)\nBBB = (AAA, DDD)\n";
+        let errors = Day8::lint(input);
+        let snippets: Vec<_> = errors
+            .iter()
+            .map(|e| e.span.as_ref().unwrap().snippet.as_str())
+            .collect();
+        assert_eq!(vec!["CCC", "DDD"], snippets);
+        assert!(Day8::lint(include_str!("../examples/day8/part_2.txt")).is_empty());
     }
 }
@@ -0,0 +1,134 @@
+//! Differential testing: solve generated inputs both with a day's
+//! solution and with its naive reference, and report where they differ.
+
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::answer::Answer;
+use crate::registry;
+use crate::solution::Part;
+
+// A generated input on which the solution and the reference disagree.
+// The seed and size reproduce the input with `gen`.
+#[derive(Debug)]
+pub struct Mismatch {
+    pub year: u32,
+    pub day: u32,
+    pub seed: u64,
+    pub size: usize,
+    pub part: Part,
+    // The solution's answer, or why it gave none.
+    pub fast: Result<Answer, String>,
+    pub naive: Answer,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fast = match &self.fast {
+            Ok(answer) => answer.to_string(),
+            Err(e) => e.clone(),
+        };
+        write!(
+            f,
+            "{} day {} part {}, seed {} size {}: expected {}, got {}",
+            self.year, self.day, self.part, self.seed, self.size, self.naive, fast
+        )
+    }
+}
+
+// A generated input that its own day fails to parse, which puts the
+// generator and the parser at odds.
+#[derive(Debug)]
+pub struct Unparsed {
+    pub year: u32,
+    pub day: u32,
+    pub seed: u64,
+    pub size: usize,
+    pub error: String,
+}
+
+impl fmt::Display for Unparsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} day {}, seed {} size {}: generated input does not parse: {}",
+            self.year, self.day, self.seed, self.size, self.error
+        )
+    }
+}
+
+// What a run of checks found.
+#[derive(Debug, Default)]
+pub struct Summary {
+    // Parts checked against a reference.
+    pub checked: usize,
+    // Parts skipped because the reference gave no answer, having none
+    // for the part or finding the input too big.
+    pub skipped: usize,
+    pub mismatches: Vec<Mismatch>,
+    pub unparsed: Vec<Unparsed>,
+}
+
+// Check the day on the input generated from `seed` and `size`. Returns
+// None when the day has no generator or no reference.
+pub fn check(year: u32, day: u32, seed: u64, size: usize, summary: &mut Summary) -> Option<()> {
+    let entry = registry::get(year, day)?;
+    let (solver, generator, reference) = (entry.solver, entry.generator?, entry.reference?);
+
+    let input = generator.input(seed, size);
+    let model = match solver.parse(&input) {
+        Ok(model) => model,
+        Err(e) => {
+            summary.unparsed.push(Unparsed {
+                year,
+                day,
+                seed,
+                size,
+                error: e.to_string(),
+            });
+            return Some(());
+        }
+    };
+    for part in Part::BOTH {
+        let Some(naive) = reference.naive(model.as_ref(), part) else {
+            summary.skipped += 1;
+            continue;
+        };
+        summary.checked += 1;
+        let fast = panic::catch_unwind(AssertUnwindSafe(|| solver.solve(model.as_ref(), part)));
+        let fast = match fast {
+            Ok(Ok(answer)) => Ok(answer),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => Err("panicked".to_string()),
+        };
+        if fast.as_ref() != Ok(&naive) {
+            summary.mismatches.push(Mismatch {
+                year,
+                day,
+                seed,
+                size,
+                part,
+                fast,
+                naive,
+            });
+        }
+    }
+    Some(())
+}
+
+// Check `count` inputs, from consecutive seeds starting at `seed` (going
+// round past u64::MAX) and sizes going round from 1 to `max_size`,
+// since small inputs make the smallest counterexamples.
+pub fn run(year: u32, day: u32, seed: u64, count: usize, max_size: usize) -> Option<Summary> {
+    let mut summary = Summary::default();
+    for i in 0..count {
+        check(
+            year,
+            day,
+            seed.wrapping_add(i as u64),
+            1 + i % max_size,
+            &mut summary,
+        )?;
+    }
+    Some(summary)
+}
@@ -0,0 +1,61 @@
+use std::{error, fmt};
+
+// Where in the input a problem was found. Lines and columns count
+// from 1, with columns in characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PuzzleError {
+    pub day: u32,
+    pub message: String,
+    pub span: Option<Span>,
+}
+
+impl PuzzleError {
+    // An error about the input as a whole, e.g. a missing section.
+    pub fn new(message: impl Into<String>) -> Self {
+        PuzzleError {
+            day: 0,
+            message: message.into(),
+            span: None,
+        }
+    }
+
+    pub fn at(line: usize, column: usize, snippet: &str, message: impl Into<String>) -> Self {
+        PuzzleError {
+            day: 0,
+            message: message.into(),
+            span: Some(Span {
+                line,
+                column,
+                snippet: snippet.to_string(),
+            }),
+        }
+    }
+
+    pub fn in_day(mut self, day: u32) -> Self {
+        self.day = day;
+        self
+    }
+}
+
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day {}", self.day)?;
+        if let Some(span) = &self.span {
+            write!(f, ", line {}, column {}", span.line, span.column)?;
+        }
+        write!(f, ": {}", self.message)?;
+        if let Some(span) = &self.span {
+            write!(f, ": '{}'", span.snippet)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for PuzzleError {}
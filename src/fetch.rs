@@ -0,0 +1,173 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs, io};
+
+use crate::config;
+use crate::http;
+use crate::runner;
+
+// Puzzle inputs are personal, so they are downloaded with the session
+// cookie of a logged in browser and kept under data/ for good: the
+// site asks tools not to download an input more than once.
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+pub const SESSION_FILE: &str = ".aoc-session";
+pub const MIN_INTERVAL: Duration = Duration::from_secs(5);
+
+// The session token, from AOC_SESSION or else the session file, which
+// the config can move from .aoc-session.
+pub fn session_token() -> io::Result<String> {
+    if let Ok(token) = env::var("AOC_SESSION") {
+        return Ok(token.trim().to_string());
+    }
+    let file = config::get()
+        .session_file
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(SESSION_FILE));
+    match fs::read_to_string(&file) {
+        Ok(token) if !token.trim().is_empty() => Ok(token.trim().to_string()),
+        _ => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "no session token: set AOC_SESSION or put it in {}",
+                file.display()
+            ),
+        )),
+    }
+}
+
+pub fn base_url(flag: Option<String>) -> String {
+    flag.or_else(|| env::var("AOC_BASE_URL").ok())
+        .or_else(|| config::get().base_url.clone())
+        .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+// Where the time of the last request is kept for rate limiting.
+pub fn stamp_path() -> PathBuf {
+    runner::data_dir().join(".last_request")
+}
+
+pub struct Client {
+    pub base_url: String,
+    pub session: String,
+    pub stamp: PathBuf,
+    pub min_interval: Duration,
+}
+
+impl Client {
+    pub fn request(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&str>,
+    ) -> io::Result<http::Response> {
+        http::throttle(&self.stamp, self.min_interval)?;
+        let cookie = format!("session={}", self.session);
+        let mut headers = vec![("Cookie", cookie.as_str())];
+        if body.is_some() {
+            headers.push(("Content-Type", "application/x-www-form-urlencoded"));
+        }
+        http::send(
+            method,
+            &format!("{}{}", self.base_url, path),
+            &headers,
+            body,
+        )
+    }
+
+    // Download the input of a day to `dest`, unless it is already there.
+    pub fn fetch(&self, year: u32, day: u32, dest: &Path) -> io::Result<usize> {
+        if fs::metadata(dest).is_ok_and(|m| m.len() > 0) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} is already downloaded", dest.display()),
+            ));
+        }
+
+        let response = self.request("GET", &format!("/{}/day/{}/input", year, day), None)?;
+        match response.status {
+            200 => (),
+            404 => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("day {} is not unlocked yet", day),
+                ))
+            }
+            400 | 500 => {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "the session token was rejected",
+                ))
+            }
+            status => {
+                return Err(io::Error::other(format!(
+                    "the server answered {}: {}",
+                    status,
+                    response.body.lines().next().unwrap_or("")
+                )))
+            }
+        }
+
+        // Write to the side first so that a failure leaves no partial input.
+        if let Some(dir) = dest.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let part = dest.with_extension("part");
+        fs::write(&part, &response.body)?;
+        fs::rename(&part, dest)?;
+        Ok(response.body.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Client;
+    use crate::http::testing::serve_once;
+    use std::fs;
+    use std::path::Path;
+    use std::time::Duration;
+
+    fn client(base_url: String, dir: &Path) -> Client {
+        Client {
+            base_url,
+            session: "53cr3t".to_string(),
+            stamp: dir.join(".last_request"),
+            min_interval: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_fetch() {
+        let dir = std::env::temp_dir().join(format!("aoc23-fetch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let dest = dir.join("day3");
+
+        let (url, server) = serve_once(200, "467..114..\n...*......\n");
+        let client = client(url, &dir);
+        assert_eq!(22, client.fetch(2023, 3, &dest).unwrap());
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /2023/day/3/input HTTP/1.0\r\n"));
+        assert!(request.contains("\r\nCookie: session=53cr3t\r\n"));
+        assert_eq!(
+            "467..114..\n...*......\n",
+            fs::read_to_string(&dest).unwrap()
+        );
+        assert!(dir.join(".last_request").exists());
+
+        // No second download once the input is there.
+        assert!(client.fetch(2023, 3, &dest).is_err());
+
+        let (url, server) = serve_once(404, "Please don't repeatedly request this endpoint");
+        let client = Client {
+            base_url: url,
+            ..client
+        };
+        assert!(client.fetch(2023, 9, &dir.join("day9")).is_err());
+        server.join().unwrap();
+        assert!(!dir.join("day9").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
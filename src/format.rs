@@ -0,0 +1,167 @@
+use std::fmt::Write;
+
+use crate::runner::{Failure, Outcome};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+// FNV-1a, so that the hash of an input stays the same across builds
+// and platforms.
+pub fn input_hash(input: &str) -> u64 {
+    input.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn status(o: &Outcome) -> &'static str {
+    match &o.answer {
+        Ok(_) => "ok",
+        Err(Failure::Error(_)) => "error",
+        Err(Failure::Panicked(_)) => "panic",
+        Err(Failure::TimedOut(_)) => "timeout",
+        Err(Failure::Skipped) => "skipped",
+    }
+}
+
+fn json_str(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+// Answers are written as strings so that big ones survive readers
+// with 53-bit numbers.
+pub fn json(outcomes: &[Outcome]) -> String {
+    let records: Vec<String> = outcomes
+        .iter()
+        .map(|o| {
+            let (answer, error) = match &o.answer {
+                Ok(answer) => (json_str(&answer.to_string()), "null".to_string()),
+                Err(failure) => ("null".to_string(), json_str(&failure.message())),
+            };
+            format!(
+                "  {{\"year\": {}, \"day\": {}, \"part\": {}, \"status\": \"{}\", \"answer\": {}, \"error\": {}, \
+                 \"duration_ns\": {}, \"input\": {}, \"input_hash\": \"{:016x}\"}}",
+                o.year,
+                o.day,
+                o.part,
+                status(o),
+                answer,
+                error,
+                o.elapsed.as_nanos(),
+                json_str(&o.input),
+                o.input_hash,
+            )
+        })
+        .collect();
+    format!("[\n{}\n]", records.join(",\n"))
+}
+
+pub fn csv(outcomes: &[Outcome]) -> String {
+    let mut out = String::from("year,day,part,status,answer,duration_ns,input,input_hash\n");
+    for o in outcomes {
+        let answer = match &o.answer {
+            Ok(answer) => answer.to_string(),
+            Err(_) => String::new(),
+        };
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{:016x}",
+            o.year,
+            o.day,
+            o.part,
+            status(o),
+            csv_field(&answer),
+            o.elapsed.as_nanos(),
+            csv_field(&o.input),
+            o.input_hash,
+        )
+        .unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{csv, input_hash, json};
+    use crate::runner::{Failure, Outcome};
+    use aoc23::answer::Answer;
+    use aoc23::solution::Part;
+    use std::time::Duration;
+
+    fn outcomes() -> Vec<Outcome> {
+        vec![
+            Outcome {
+                year: 2023,
+                day: 7,
+                part: Part::One,
+                input: "data/day7".to_string(),
+                input_hash: input_hash("32T3K 765\n"),
+                answer: Ok(Answer::from(6440u64)),
+                elapsed: Duration::from_micros(12),
+            },
+            Outcome {
+                year: 2023,
+                day: 7,
+                part: Part::Two,
+                input: "my \"odd\", input".to_string(),
+                input_hash: input_hash(""),
+                answer: Err(Failure::Panicked("not yet implemented".to_string())),
+                elapsed: Duration::from_nanos(5),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_input_hash() {
+        assert_eq!(0xcbf29ce484222325, input_hash(""));
+        assert_eq!(0xaf63dc4c8601ec8c, input_hash("a"));
+    }
+
+    #[test]
+    fn test_json() {
+        let json = json(&outcomes());
+        assert!(json.contains(
+            r#"{"year": 2023, "day": 7, "part": 1, "status": "ok", "answer": "6440", "error": null, "duration_ns": 12000, "input": "data/day7""#
+        ));
+        assert!(json.contains(
+            r#""status": "panic", "answer": null, "error": "panicked: not yet implemented""#
+        ));
+        assert!(json.contains(r#""input": "my \"odd\", input""#));
+    }
+
+    #[test]
+    fn test_csv() {
+        let csv = csv(&outcomes());
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(3, rows.len());
+        assert!(rows[1].starts_with("2023,7,1,ok,6440,12000,data/day7,"));
+        assert_eq!(
+            "2023,7,2,panic,,5,\"my \"\"odd\"\", input\",cbf29ce484222325",
+            rows[2]
+        );
+    }
+}
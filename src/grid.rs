@@ -0,0 +1,39 @@
+//! Helpers for puzzles laid out on a grid of rows and columns.
+
+// The up to eight cells around (row, col) that lie on a grid of `rows`
+// by `cols`, diagonals included.
+pub fn around(
+    row: usize,
+    col: usize,
+    rows: usize,
+    cols: usize,
+) -> impl Iterator<Item = (usize, usize)> {
+    [-1, 0, 1]
+        .into_iter()
+        .flat_map(|dr| [-1, 0, 1].into_iter().map(move |dc| (dr, dc)))
+        .filter(|&d| d != (0, 0))
+        .filter_map(move |(dr, dc)| {
+            let r = row.checked_add_signed(dr)?;
+            let c = col.checked_add_signed(dc)?;
+            (r < rows && c < cols).then_some((r, c))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::around;
+
+    #[test]
+    fn test_around() {
+        assert_eq!(8, around(1, 1, 3, 3).count());
+        assert_eq!(
+            vec![(0, 1), (1, 0), (1, 1)],
+            around(0, 0, 3, 3).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(1, 1), (1, 2), (2, 1)],
+            around(2, 2, 3, 3).collect::<Vec<_>>()
+        );
+        assert_eq!(0, around(0, 0, 1, 1).count());
+    }
+}
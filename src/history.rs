@@ -0,0 +1,272 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::bench::{Measurement, Stats};
+
+// Benchmark results are appended to a tab separated history file, one
+// row per day and stage. A run is every row of one `bench --record`,
+// labelled with the git commit it measured when there is one, and is
+// of a single year.
+
+pub const DEFAULT_PATH: &str = "bench_history.tsv";
+
+const HEADER: &str =
+    "run\ttimestamp_ms\tyear\tday\tstage\titers\tmin_ns\tmedian_ns\tmean_ns\tstddev_ns";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub run: String,
+    pub timestamp: u64,
+    pub year: u32,
+    pub day: u32,
+    pub stage: String,
+    pub stats: Stats,
+}
+
+impl Record {
+    fn to_row(&self) -> String {
+        let s = &self.stats;
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.run,
+            self.timestamp,
+            self.year,
+            self.day,
+            self.stage,
+            s.iters,
+            s.min.as_nanos(),
+            s.median.as_nanos(),
+            s.mean.as_nanos(),
+            s.stddev.as_nanos(),
+        )
+    }
+
+    fn from_row(row: &str) -> Option<Self> {
+        let cols: Vec<&str> = row.split('\t').collect();
+        if cols.len() != 10 {
+            return None;
+        }
+        let nanos = |s: &str| s.parse::<u64>().ok().map(Duration::from_nanos);
+        Some(Record {
+            run: cols[0].to_string(),
+            timestamp: cols[1].parse().ok()?,
+            year: cols[2].parse().ok()?,
+            day: cols[3].parse().ok()?,
+            stage: cols[4].to_string(),
+            stats: Stats {
+                iters: cols[5].parse().ok()?,
+                min: nanos(cols[6])?,
+                median: nanos(cols[7])?,
+                mean: nanos(cols[8])?,
+                stddev: nanos(cols[9])?,
+            },
+        })
+    }
+
+    // Rows of one run share the run label and timestamp.
+    fn same_run(&self, other: &Record) -> bool {
+        self.run == other.run && self.timestamp == other.timestamp
+    }
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    let out = Command::new("git").args(args).output().ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+// The current commit, marked dirty when the tree has local changes,
+// or the time of the run outside of a git checkout.
+pub fn run_label(timestamp: u64) -> String {
+    match git(&["rev-parse", "--short", "HEAD"]) {
+        Some(commit) => match git(&["status", "--porcelain", "--untracked-files=no"]) {
+            Some(status) if !status.is_empty() => format!("{}-dirty", commit),
+            _ => commit,
+        },
+        None => format!("t{}", timestamp),
+    }
+}
+
+// Milliseconds since the epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+pub fn append(path: &Path, measurements: &[Measurement]) -> io::Result<String> {
+    let timestamp = now();
+    let run = run_label(timestamp);
+    let new = !path.exists();
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if new {
+        writeln!(file, "{}", HEADER)?;
+    }
+    for m in measurements {
+        let record = Record {
+            run: run.clone(),
+            timestamp,
+            year: m.year,
+            day: m.day,
+            stage: m.stage.to_string(),
+            stats: m.stats,
+        };
+        writeln!(file, "{}", record.to_row())?;
+    }
+    Ok(run)
+}
+
+pub fn load(path: &Path) -> io::Result<Vec<Record>> {
+    let text = fs::read_to_string(path)?;
+    text.lines()
+        .enumerate()
+        .filter(|(_, row)| !row.trim().is_empty() && *row != HEADER)
+        .map(|(i, row)| {
+            Record::from_row(row).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: line {}: malformed record", path.display(), i + 1),
+                )
+            })
+        })
+        .collect()
+}
+
+// Records grouped by run, oldest first.
+pub fn runs(records: &[Record]) -> Vec<Vec<&Record>> {
+    let mut runs: Vec<Vec<&Record>> = vec![];
+    for record in records {
+        match runs.iter_mut().find(|run| run[0].same_run(record)) {
+            Some(run) => run.push(record),
+            None => runs.push(vec![record]),
+        }
+    }
+    runs
+}
+
+pub struct Change<'a> {
+    pub baseline: &'a Record,
+    pub current: &'a Record,
+    // Relative change of the median, 0.1 being 10% slower.
+    pub ratio: f64,
+}
+
+impl Change<'_> {
+    pub fn is_regression(&self, threshold: f64) -> bool {
+        self.ratio > threshold
+    }
+}
+
+pub fn compare<'a>(baseline: &[&'a Record], current: &[&'a Record]) -> Vec<Change<'a>> {
+    current
+        .iter()
+        .filter_map(|cur| {
+            let base = baseline
+                .iter()
+                .find(|b| (b.year, b.day, &b.stage) == (cur.year, cur.day, &cur.stage))?;
+            let before = base.stats.median.as_secs_f64();
+            let after = cur.stats.median.as_secs_f64();
+            let ratio = if before > 0.0 {
+                after / before - 1.0
+            } else {
+                0.0
+            };
+            Some(Change {
+                baseline: base,
+                current: cur,
+                ratio,
+            })
+        })
+        .collect()
+}
+
+// Print the comparison and tell whether anything got slower than the
+// threshold allows.
+pub fn print_changes(changes: &[Change], threshold: f64) -> bool {
+    let mut regressed = false;
+    println!(
+        "{:>3}  {:<5}  {:>10}  {:>10}  {:>8}",
+        "day", "stage", "baseline", "current", "change"
+    );
+    for c in changes {
+        let flag = if c.is_regression(threshold) {
+            regressed = true;
+            "  SLOWER"
+        } else {
+            ""
+        };
+        println!(
+            "{:>3}  {:<5}  {:>10}  {:>10}  {:>+7.1}%{}",
+            c.current.day,
+            c.current.stage,
+            format!("{:.2?}", c.baseline.stats.median),
+            format!("{:.2?}", c.current.stats.median),
+            c.ratio * 100.0,
+            flag,
+        );
+    }
+    regressed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare, runs, Record};
+    use crate::bench::Stats;
+    use std::time::Duration;
+
+    fn record(run: &str, timestamp: u64, day: u32, median_us: u64) -> Record {
+        let us = Duration::from_micros(median_us);
+        Record {
+            run: run.to_string(),
+            timestamp,
+            year: 2023,
+            day,
+            stage: "part1".to_string(),
+            stats: Stats {
+                iters: 10,
+                min: us,
+                median: us,
+                mean: us,
+                stddev: Duration::ZERO,
+            },
+        }
+    }
+
+    #[test]
+    fn test_row_round_trip() {
+        let r = record("abc123-dirty", 1700000000, 5, 250);
+        assert_eq!(Some(r.clone()), Record::from_row(&r.to_row()));
+        assert_eq!(None, Record::from_row("abc123\t1\t5"));
+
+        let mut r = record("abc123", 1700000000, 5, 250);
+        r.year = 2024;
+        assert_eq!(Some(r.clone()), Record::from_row(&r.to_row()));
+    }
+
+    #[test]
+    fn test_compare_runs() {
+        let records = vec![
+            record("abc123", 1, 3, 100),
+            record("abc123", 1, 5, 100),
+            record("abc123", 2, 3, 100),
+            record("abc123", 2, 5, 125),
+        ];
+        let runs = runs(&records);
+        assert_eq!(2, runs.len());
+
+        let changes = compare(&runs[0], &runs[1]);
+        assert_eq!(2, changes.len());
+        assert!(!changes[0].is_regression(0.1));
+        assert!(changes[1].is_regression(0.1));
+        assert!(!changes[1].is_regression(0.3));
+
+        let mut other = record("def456", 3, 3, 500);
+        other.year = 2024;
+        assert!(compare(&runs[1], &[&other]).is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use crate::history;
+
+// Just enough HTTP for talking to the puzzle site. Plain http:// URLs,
+// as used by the test servers, are spoken over a socket here; https://
+// ones go through curl since std has no TLS.
+
+pub const USER_AGENT: &str = "aoc23 puzzle runner";
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+pub fn send(
+    method: &str,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: Option<&str>,
+) -> io::Result<Response> {
+    if let Some(rest) = url.strip_prefix("http://") {
+        send_plain(method, rest, headers, body)
+    } else if url.starts_with("https://") {
+        send_curl(method, url, headers, body)
+    } else {
+        Err(invalid(format!("unsupported URL '{}'", url)))
+    }
+}
+
+// HTTP/1.0 keeps the server from chunking the body, which is then
+// everything up to the end of the connection.
+fn send_plain(
+    method: &str,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: Option<&str>,
+) -> io::Result<Response> {
+    let (host, path) = match url.find('/') {
+        Some(i) => (&url[..i], &url[i..]),
+        None => (url, "/"),
+    };
+    let addr = if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{}:80", host)
+    };
+
+    let mut request = format!(
+        "{} {} HTTP/1.0\r\nHost: {}\r\nUser-Agent: {}\r\nConnection: close\r\n",
+        method, path, host, USER_AGENT
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    if let Some(body) = body {
+        request.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    request.push_str("\r\n");
+    request.push_str(body.unwrap_or(""));
+
+    let mut stream = TcpStream::connect(&addr)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.write_all(request.as_bytes())?;
+    let mut raw = vec![];
+    stream.read_to_end(&mut raw)?;
+    parse_response(&raw)
+}
+
+fn parse_response(raw: &[u8]) -> io::Result<Response> {
+    let raw = String::from_utf8_lossy(raw);
+    let (head, body) = raw
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| invalid("truncated HTTP response"))?;
+    let status = head
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| invalid("malformed HTTP status line"))?;
+    Ok(Response {
+        status,
+        body: body.to_string(),
+    })
+}
+
+// The headers go through stdin so that the session cookie does not
+// show up in the process list.
+fn send_curl(
+    method: &str,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: Option<&str>,
+) -> io::Result<Response> {
+    let mut cmd = Command::new("curl");
+    cmd.args(["--silent", "--show-error", "--request", method])
+        .args(["--user-agent", USER_AGENT, "--header", "@-"])
+        .args(["--max-time", &TIMEOUT.as_secs().to_string()])
+        .args(["--write-out", "\n%{http_code}"]);
+    if let Some(body) = body {
+        cmd.args(["--data-binary", body]);
+    }
+    let mut child = cmd
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| io::Error::new(e.kind(), format!("running curl: {}", e)))?;
+
+    let mut stdin = child.stdin.take().unwrap();
+    for (name, value) in headers {
+        writeln!(stdin, "{}: {}", name, value)?;
+    }
+    drop(stdin);
+
+    let out = child.wait_with_output()?;
+    if !out.status.success() {
+        return Err(io::Error::other(format!("curl failed on {}", url)));
+    }
+    let out = String::from_utf8_lossy(&out.stdout);
+    let (body, status) = out
+        .rsplit_once('\n')
+        .ok_or_else(|| invalid("curl gave no status"))?;
+    Ok(Response {
+        status: status.parse().map_err(|_| invalid("curl gave no status"))?,
+        body: body.to_string(),
+    })
+}
+
+// Wait until `min_interval` has passed since the request recorded in
+// the stamp file, then record this one there.
+pub fn throttle(stamp: &Path, min_interval: Duration) -> io::Result<()> {
+    let last = fs::read_to_string(stamp)
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok());
+    if let Some(last) = last {
+        // Stamps are in whole milliseconds, so up to one more may have
+        // been counted than really passed.
+        let since = Duration::from_millis(history::now().saturating_sub(last + 1));
+        if since < min_interval {
+            let wait = min_interval - since;
+            eprintln!("waiting {:.1?} before the next request", wait);
+            thread::sleep(wait);
+        }
+    }
+    fs::write(stamp, history::now().to_string())
+}
+
+#[cfg(test)]
+pub mod testing {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread::{self, JoinHandle};
+
+    // Serve one canned response on a local port, handing back the base
+    // URL and the request the server received.
+    pub fn serve_once(status: u16, body: &str) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let response = format!(
+            "HTTP/1.1 {} Whatever\r\nContent-Type: text/plain\r\n\r\n{}",
+            status, body
+        );
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = vec![];
+            let mut buf = [0; 4096];
+            // Read the head, then as much body as it announces.
+            loop {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let len = head
+                        .lines()
+                        .find_map(|l| l.strip_prefix("Content-Length: "))
+                        .map_or(0, |n| n.parse().unwrap());
+                    if body.len() >= len {
+                        break;
+                    }
+                }
+                if n == 0 {
+                    break;
+                }
+            }
+            stream.write_all(response.as_bytes()).unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        (url, handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::serve_once;
+    use super::{parse_response, send, throttle};
+    use std::time::{Duration, Instant};
+    use std::{env, fs, process};
+
+    #[test]
+    fn test_send_plain() {
+        let (url, server) = serve_once(200, "hello\n");
+        let response = send(
+            "POST",
+            &format!("{}/path", url),
+            &[("Cookie", "session=abc")],
+            Some("a=1"),
+        )
+        .unwrap();
+        assert_eq!(200, response.status);
+        assert_eq!("hello\n", response.body);
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /path HTTP/1.0\r\n"));
+        assert!(request.contains("\r\nCookie: session=abc\r\n"));
+        assert!(request.ends_with("\r\n\r\na=1"));
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let response = parse_response(b"HTTP/1.1 404 Not Found\r\nX: y\r\n\r\ngone").unwrap();
+        assert_eq!(404, response.status);
+        assert_eq!("gone", response.body);
+        assert!(parse_response(b"HTTP/1.1 200 OK\r\n").is_err());
+        assert!(send("GET", "ftp://example.com", &[], None).is_err());
+    }
+
+    #[test]
+    fn test_throttle() {
+        let stamp = env::temp_dir().join(format!("aoc23-stamp-{}", process::id()));
+        let interval = Duration::from_millis(50);
+        let start = Instant::now();
+        throttle(&stamp, interval).unwrap();
+        throttle(&stamp, interval).unwrap();
+        assert!(start.elapsed() >= interval);
+        fs::remove_file(&stamp).unwrap();
+    }
+}
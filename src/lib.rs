@@ -0,0 +1,26 @@
+//! Advent of Code 2023 solvers.
+//!
+//! Every day lives in its own `dayN` module with a `DayN` type
+//! implementing [`solution::Solution`], which parses the puzzle input
+//! into the day's model and solves both parts from it. The
+//! [`registry`] lists them for tools that work on any day.
+
+pub mod answer;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod differential;
+pub mod error;
+pub mod grid;
+pub mod params;
+pub mod parse;
+pub mod ranges;
+pub mod registry;
+pub mod rng;
+pub mod solution;
+pub mod toml;
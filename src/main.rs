@@ -1,57 +1,299 @@
-use std::{env, fs, io};
+use std::path::PathBuf;
+use std::{env, process};
 
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
+use answers::Answers;
+use aoc23::{differential, registry};
+use bench::BenchOpts;
+use cli::{Command, Days, RunArgs};
+use format::Format;
 
-fn main() -> io::Result<()> {
-    let mut args = env::args();
-    let file_name = args.nth(1).expect("missing filename");
-    let input = fs::read_to_string(file_name)?;
+mod answers;
+mod bench;
+mod cli;
+mod config;
+mod fetch;
+mod format;
+mod history;
+mod http;
+mod repl;
+mod runner;
+mod scaffold;
+mod submit;
+mod verify;
+mod watch;
 
-    let day = args.next().expect("missing day");
+fn main() {
+    if let Err(e) = config::init() {
+        fail(e);
+    }
+    let cli::Cli { year, command } = match cli::parse(env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, cli::USAGE);
+            process::exit(2);
+        }
+    };
 
-    match day.as_str() {
-        "1" => {
-            println!("part1 answer: {}", day1::part_one(&input));
-            println!("part2 answer: {}", day1::part_two(&input));
+    match command {
+        Command::List => {
+            for solver in registry::days(year) {
+                println!("day {}: {}", solver.day(), solver.title());
+            }
+        }
+        Command::Run(args) => {
+            let report = runner::run(&args).unwrap_or_else(|e| fail(e));
+            match args.format {
+                Format::Text => {
+                    runner::print_table(&report);
+                    runner::print_traces(&report);
+                }
+                Format::Json => println!("{}", format::json(&report.outcomes)),
+                Format::Csv => print!("{}", format::csv(&report.outcomes)),
+            }
+            if runner::report_failures(&report.outcomes) {
+                process::exit(1);
+            }
+        }
+        Command::Verify(args) => {
+            let path = answers_path(year, args.answers);
+            let mut answers = Answers::load(&path).unwrap_or_else(|e| fail(e));
+            let run = RunArgs {
+                year,
+                days: args.days,
+                part: None,
+                input: None,
+                example: false,
+                format: Format::Text,
+                timeout: args.timeout,
+                threads: None,
+                explain: false,
+            };
+            let outcomes = runner::run(&run).unwrap_or_else(|e| fail(e)).outcomes;
+            let passed = verify::report(&outcomes, &answers);
+            if args.record && verify::record_missing(&outcomes, &mut answers) > 0 {
+                answers.save(&path).unwrap_or_else(|e| fail(e));
+                println!("recorded new answers in {}", path.display());
+            }
+            if !passed {
+                process::exit(1);
+            }
         }
-        "2" => {
-            println!("part1 answer: {}", day2::part_one(&input));
-            println!("part2 answer: {}", day2::part_two(&input));
+        Command::Bench(args) => {
+            let opts = BenchOpts {
+                warmup: args.warmup,
+                iters: args.iters,
+                timeout: args.run.timeout,
+            };
+            let mut measurements = vec![];
+            for job in runner::jobs(&args.run).unwrap_or_else(|e| fail(e)) {
+                measurements.extend(bench::bench_day(
+                    job.solver,
+                    &job.input,
+                    &job.params,
+                    &job.parts,
+                    &opts,
+                ));
+            }
+            bench::print_table(&measurements);
+            if args.record {
+                let path = history_path(args.history);
+                let run = history::append(&path, &measurements).unwrap_or_else(|e| fail(e));
+                println!("recorded run {} in {}", run, path.display());
+            }
         }
-        "3" => {
-            println!("part1 answer: {}", day3::part_one(&input));
-            println!("part2 answer: {}", day3::part_two(&input));
+        Command::Compare(args) => {
+            let path = history_path(args.history);
+            let records: Vec<_> = history::load(&path)
+                .unwrap_or_else(|e| fail(e))
+                .into_iter()
+                .filter(|r| r.year == year)
+                .collect();
+            let runs = history::runs(&records);
+            let (current, earlier) = match runs.split_last() {
+                Some((current, earlier)) if !earlier.is_empty() => (current, earlier),
+                _ => fail(format!(
+                    "{}: needs at least two runs of {}",
+                    path.display(),
+                    year
+                )),
+            };
+            let baseline = match &args.baseline {
+                Some(label) => earlier
+                    .iter()
+                    .rev()
+                    .find(|run| run[0].run == *label)
+                    .unwrap_or_else(|| fail(format!("no run {} before the last", label))),
+                None => &earlier[earlier.len() - 1],
+            };
+            println!("baseline {}, current {}", baseline[0].run, current[0].run);
+            let changes = history::compare(baseline, current);
+            if history::print_changes(&changes, args.threshold) {
+                process::exit(1);
+            }
         }
-        "4" => {
-            println!("part1 answer: {}", day4::part_one(&input));
-            println!("part2 answer: {}", day4::part_two(&input));
+        Command::Watch(args) => {
+            watch::watch(year, args.day, args.example).unwrap_or_else(|e| fail(e))
         }
-        "5" => {
-            println!("part1 answer: {}", day5::part_one(&input));
-            println!("part2 answer: {}", day5::part_two(&input));
+        Command::NewDay(args) => {
+            for path in scaffold::new_day(year, args.day, &args.title).unwrap_or_else(|e| fail(e)) {
+                println!("wrote {}", path);
+            }
         }
-        "6" => {
-            println!("part1 answer: {}", day6::part_one(&input));
-            println!("part2 answer: {}", day6::part_two(&input));
+        Command::Fetch(args) => {
+            let client = fetch::Client {
+                base_url: fetch::base_url(args.base_url),
+                session: fetch::session_token().unwrap_or_else(|e| fail(e)),
+                stamp: fetch::stamp_path(),
+                min_interval: fetch::MIN_INTERVAL,
+            };
+            let dest = PathBuf::from(runner::data_path(year, args.day));
+            let bytes = client
+                .fetch(year, args.day, &dest)
+                .unwrap_or_else(|e| fail(e));
+            println!("wrote {} bytes to {}", bytes, dest.display());
         }
-        "7" => {
-            println!("part1 answer: {}", day7::part_one(&input));
-            println!("part2 answer: {}", day7::part_two(&input));
+        Command::Gen(args) => {
+            let generator = registry::get(year, args.day)
+                .and_then(|d| d.generator)
+                .unwrap_or_else(|| fail(format!("no generator for day {} of {}", args.day, year)));
+            let seed = args.seed.unwrap_or_else(history::now);
+            eprintln!("seed {}", seed);
+            print!("{}", generator.input(seed, args.size));
         }
-        "8" => {
-            println!("part1 answer: {}", day8::part_one(&input));
-            println!("part2 answer: {}", day8::part_two(&input));
+        Command::DiffTest(args) => {
+            let summary = differential::run(year, args.day, args.seed, args.count, args.max_size)
+                .unwrap_or_else(|| fail(format!("no reference to check day {} against", args.day)));
+            for unparsed in &summary.unparsed {
+                println!("{}", unparsed);
+            }
+            for mismatch in &summary.mismatches {
+                println!("{}", mismatch);
+            }
+            println!(
+                "day {}: {} parts checked, {} skipped, {} mismatches, {} inputs that do not parse",
+                args.day,
+                summary.checked,
+                summary.skipped,
+                summary.mismatches.len(),
+                summary.unparsed.len()
+            );
+            if !summary.mismatches.is_empty() || !summary.unparsed.is_empty() {
+                process::exit(1);
+            }
         }
-        _ => {
-            panic!("unimplemented!");
+        Command::Lint(args) => {
+            let linter = registry::get(year, args.day)
+                .map(|d| d.linter)
+                .unwrap_or_else(|| {
+                    fail(format!("day {} of {} is not implemented", args.day, year))
+                });
+            let path = args
+                .input
+                .unwrap_or_else(|| runner::data_path(year, args.day));
+            let input = runner::read_input(&path)
+                .unwrap_or_else(|e| fail(format!("reading {}: {}", path, e)));
+            let errors = linter.lint(&input);
+            for e in &errors {
+                println!("{}", e);
+            }
+            match errors.len() {
+                0 => println!("{}: ok", path),
+                1 => println!("{}: 1 problem", path),
+                n => println!("{}: {} problems", path, n),
+            }
+            if !errors.is_empty() {
+                process::exit(1);
+            }
+        }
+        Command::Repl(args) => {
+            let solver = registry::find(year, args.day).unwrap_or_else(|| {
+                fail(format!("day {} of {} is not implemented", args.day, year))
+            });
+            let path = args
+                .input
+                .unwrap_or_else(|| runner::data_path(year, args.day));
+            let input = runner::read_input(&path)
+                .unwrap_or_else(|e| fail(format!("reading {}: {}", path, e)));
+            let model = solver
+                .parse_with(&input, &config::get().params(year, args.day))
+                .unwrap_or_else(|e| fail(format!("{}: {}", path, e)));
+            eprintln!("day {}: parsed {}, try help", args.day, path);
+            let session = repl::Session {
+                solver,
+                querier: registry::get(year, args.day).and_then(|d| d.querier),
+                model: model.as_ref(),
+            };
+            repl::run(&session).unwrap_or_else(|e| fail(e));
+        }
+        Command::Submit(args) => {
+            let run = RunArgs {
+                year,
+                days: Days::One(args.day),
+                part: Some(args.part),
+                input: None,
+                example: false,
+                format: Format::Text,
+                timeout: config::get().timeout.unwrap_or(cli::DEFAULT_TIMEOUT),
+                threads: None,
+                explain: false,
+            };
+            let outcome = runner::run(&run)
+                .unwrap_or_else(|e| fail(e))
+                .outcomes
+                .remove(0);
+            let answer = outcome.answer.unwrap_or_else(|failure| {
+                fail(format!("{}: {}", outcome.input, failure.message()))
+            });
+
+            let answers_path = answers_path(year, args.answers);
+            let mut answers = Answers::load(&answers_path).unwrap_or_else(|e| fail(e));
+            let mut attempts =
+                submit::Attempts::load(&runner::year_data_path(year, "attempts.tsv"))
+                    .unwrap_or_else(|e| fail(e));
+            let now = history::now();
+            if let Some(reason) =
+                submit::refusal(&attempts, &answers, args.day, args.part, &answer, now)
+            {
+                fail(format!("not submitting: {}", reason));
+            }
+
+            let client = fetch::Client {
+                base_url: fetch::base_url(args.base_url),
+                session: fetch::session_token().unwrap_or_else(|e| fail(e)),
+                stamp: fetch::stamp_path(),
+                min_interval: fetch::MIN_INTERVAL,
+            };
+            println!("day {} part {}: submitting {}", args.day, args.part, answer);
+            let reply = submit::submit(&client, &mut attempts, year, args.day, args.part, &answer)
+                .unwrap_or_else(|e| fail(e));
+            match reply.wait {
+                Some(wait) => {
+                    println!("{} (wait {:?} before the next answer)", reply.verdict, wait)
+                }
+                None => println!("{}", reply.verdict),
+            }
+            if reply.verdict == submit::Verdict::Correct {
+                answers.set(args.day, args.part, &answer);
+                answers.save(&answers_path).unwrap_or_else(|e| fail(e));
+                println!("recorded in {}", answers_path.display());
+            } else {
+                println!("{}", reply.message);
+                process::exit(1);
+            }
         }
     }
-    Ok(())
+}
+
+fn answers_path(year: u32, path: Option<String>) -> PathBuf {
+    path.map(PathBuf::from)
+        .unwrap_or_else(|| runner::year_data_path(year, "answers.toml"))
+}
+
+fn history_path(path: Option<String>) -> PathBuf {
+    PathBuf::from(path.unwrap_or_else(|| history::DEFAULT_PATH.to_string()))
+}
+
+fn fail(e: impl std::fmt::Display) -> ! {
+    eprintln!("error: {}", e);
+    process::exit(1);
 }
@@ -0,0 +1,29 @@
+//! Puzzle parameters that the config file can override, such as the
+//! cubes in the bag of day 2. A day lists the names of its parameters
+//! in `Solution::PARAMS` and is handed their values by
+//! `Solution::parse_with`, falling back to the puzzle's own numbers.
+
+use std::collections::BTreeMap;
+
+// The overridden parameters of one day, by name.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Params(BTreeMap<String, i64>);
+
+impl Params {
+    pub fn new() -> Self {
+        Params::default()
+    }
+
+    pub fn set(&mut self, name: &str, value: i64) {
+        self.0.insert(name.to_string(), value);
+    }
+
+    pub fn with(mut self, name: &str, value: i64) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    pub fn get(&self, name: &str, default: i64) -> i64 {
+        self.0.get(name).copied().unwrap_or(default)
+    }
+}
@@ -0,0 +1,94 @@
+use std::str::FromStr;
+
+use crate::error::PuzzleError;
+
+// A line of puzzle input and its 1-based line number. Errors raised
+// through it point at the offending part of the line, which must be
+// a slice of text for the column to come out right.
+#[derive(Debug, Clone, Copy)]
+pub struct Line<'a> {
+    pub no: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    // A string parsed on its own, e.g. by a FromStr impl. Errors come
+    // out relative to it and are moved to the real line by `within`.
+    pub fn detached(text: &'a str) -> Self {
+        Line { no: 0, text }
+    }
+
+    fn column(&self, snippet: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        let at = snippet.as_ptr() as usize;
+        if start <= at && at + snippet.len() <= start + self.text.len() {
+            self.text[..at - start].chars().count() + 1
+        } else {
+            1
+        }
+    }
+
+    pub fn error(&self, snippet: &str, message: impl Into<String>) -> PuzzleError {
+        PuzzleError::at(self.no, self.column(snippet), snippet, message)
+    }
+
+    // Place an error raised on a detached part of this line.
+    pub fn within(&self, part: &str, mut err: PuzzleError) -> PuzzleError {
+        if let Some(span) = &mut err.span {
+            span.line = self.no;
+            span.column += self.column(part) - 1;
+        }
+        err
+    }
+
+    pub fn split_once(&self, s: &'a str, delim: char) -> Result<(&'a str, &'a str), PuzzleError> {
+        s.split_once(delim)
+            .ok_or_else(|| self.error(s, format!("expected '{}'", delim)))
+    }
+
+    pub fn number<T: FromStr>(&self, s: &str) -> Result<T, PuzzleError> {
+        s.parse::<T>()
+            .map_err(|_| self.error(s, "expected a number"))
+    }
+
+    pub fn numbers<T: FromStr>(&self, s: &str) -> Result<Vec<T>, PuzzleError> {
+        s.split_whitespace().map(|n| self.number(n)).collect()
+    }
+}
+
+// Every line of the input, blank ones included.
+pub fn all_lines(input: &str) -> impl Iterator<Item = Line<'_>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, text)| Line { no: i + 1, text })
+}
+
+// The non-blank lines of the input.
+pub fn lines(input: &str) -> impl Iterator<Item = Line<'_>> {
+    all_lines(input).filter(|l| !l.text.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lines, Line};
+
+    #[test]
+    fn test_error_columns() {
+        let line = lines("\nGame 3: 8 green, 6 purple").next().unwrap();
+        assert_eq!(2, line.no);
+
+        let (_, draws) = line.split_once(line.text, ':').unwrap();
+        let err = line.error(&draws[12..], "unknown color");
+        let span = err.span.unwrap();
+        assert_eq!(
+            (2, 20, "purple"),
+            (span.line, span.column, span.snippet.as_str())
+        );
+
+        let part = draws.trim();
+        let err = Line::detached(part).number::<u64>(&part[9..]).unwrap_err();
+        let span = line.within(part, err).span.unwrap();
+        assert_eq!((2, 18), (span.line, span.column));
+    }
+}
@@ -0,0 +1,44 @@
+//! Helpers for half-open ranges of numbers, as used by puzzles that map
+//! whole ranges of values at once.
+
+use std::ops::Range;
+
+pub fn overlaps(a: &Range<u64>, b: &Range<u64>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+// Split `range` into the part that lies within `by`, if any, and the
+// parts before and after it that do not.
+pub fn split(range: &Range<u64>, by: &Range<u64>) -> (Option<Range<u64>>, Vec<Range<u64>>) {
+    if !overlaps(range, by) {
+        return (None, vec![range.clone()]);
+    }
+    let inside = range.start.max(by.start)..range.end.min(by.end);
+    let outside = [range.start..inside.start, inside.end..range.end]
+        .into_iter()
+        .filter(|r| !r.is_empty())
+        .collect();
+    (Some(inside), outside)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{overlaps, split};
+    use std::ops::Range;
+
+    // Clippy takes vec![a..b] for a mistaken vec of a..b's numbers.
+    fn one(range: Range<u64>) -> Vec<Range<u64>> {
+        vec![range]
+    }
+
+    #[test]
+    fn test_split() {
+        assert_eq!((Some(5..8), one(2..5)), split(&(2..8), &(5..10)));
+        assert_eq!((Some(5..8), one(8..12)), split(&(5..12), &(0..8)));
+        assert_eq!((Some(4..6), vec![2..4, 6..9]), split(&(2..9), &(4..6)));
+        assert_eq!((Some(3..5), vec![]), split(&(3..5), &(0..10)));
+        assert_eq!((None, one(0..5)), split(&(0..5), &(5..10)));
+        assert!(overlaps(&(0..5), &(4..6)));
+        assert!(!overlaps(&(0..5), &(5..6)));
+    }
+}
@@ -0,0 +1,111 @@
+use std::path::{Path, PathBuf};
+
+use crate::solution::{Explainer, InputGenerator, Linter, Querier, ReferenceSolver, Solver};
+use crate::{day1, day2, day3, day4, day5, day6, day7, day8};
+
+// A registered day: its solver, its linter, which every day has, and
+// whichever optional capabilities it implements.
+pub struct Day {
+    pub solver: &'static dyn Solver,
+    pub generator: Option<&'static dyn InputGenerator>,
+    pub linter: &'static dyn Linter,
+    pub reference: Option<&'static dyn ReferenceSolver>,
+    pub explainer: Option<&'static dyn Explainer>,
+    pub querier: Option<&'static dyn Querier>,
+}
+
+// Register a day along with the capabilities it implements, e.g.
+// `day!(day5::Day5, generator, reference)`.
+macro_rules! day {
+    ($solution:path $(, $capability:ident)*) => {
+        Day {
+            $($capability: Some(&$solution),)*
+            ..Day {
+                solver: &$solution,
+                generator: None,
+                linter: &$solution,
+                reference: None,
+                explainer: None,
+                querier: None,
+            }
+        }
+    };
+}
+
+static DAYS: &[Day] = &[
+    day!(day1::Day1, generator, explainer),
+    day!(day2::Day2, generator, explainer, querier),
+    day!(day3::Day3, generator, explainer),
+    day!(day4::Day4, generator, reference, explainer),
+    day!(day5::Day5, generator, reference, explainer, querier),
+    day!(day6::Day6, generator, reference, explainer),
+    day!(day7::Day7, generator, explainer, querier),
+    day!(day8::Day8, generator, reference, explainer, querier),
+];
+
+// The year of the solvers in src/day<n>.rs, whose inputs may stay in
+// the flat data/day<n> layout. Other years live in src/y<year>/.
+pub const DEFAULT_YEAR: u32 = 2023;
+
+// Where a file of a year goes under `root`: root/<year>/<name>, except
+// that the default year may keep it in root itself, as in the layout
+// from before there were years. Each file falls back on its own, so
+// that they can move one at a time; a new one goes in root/<year> once
+// that directory exists.
+pub fn year_path(root: &Path, year: u32, name: &str) -> PathBuf {
+    let dir = root.join(year.to_string());
+    let path = dir.join(name);
+    if year != DEFAULT_YEAR || path.exists() {
+        return path;
+    }
+    let flat = root.join(name);
+    if flat.exists() || !dir.is_dir() {
+        flat
+    } else {
+        path
+    }
+}
+
+pub fn all() -> &'static [Day] {
+    DAYS
+}
+
+// The solvers of one year, in day order.
+pub fn days(year: u32) -> Vec<&'static dyn Solver> {
+    DAYS.iter()
+        .map(|d| d.solver)
+        .filter(|s| s.year() == year)
+        .collect()
+}
+
+pub fn get(year: u32, day: u32) -> Option<&'static Day> {
+    DAYS.iter()
+        .find(|d| d.solver.year() == year && d.solver.day() == day)
+}
+
+pub fn find(year: u32, day: u32) -> Option<&'static dyn Solver> {
+    get(year, day).map(|d| d.solver)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{year_path, DEFAULT_YEAR};
+    use std::{env, fs, process};
+
+    #[test]
+    fn test_year_path() {
+        let root = env::temp_dir().join(format!("aoc23-years-{}", process::id()));
+        let year = root.join(DEFAULT_YEAR.to_string());
+        fs::create_dir_all(&year).unwrap();
+        fs::write(year.join("day1"), "").unwrap();
+        fs::write(root.join("day2"), "").unwrap();
+
+        assert_eq!(year.join("day1"), year_path(&root, DEFAULT_YEAR, "day1"));
+        assert_eq!(root.join("day2"), year_path(&root, DEFAULT_YEAR, "day2"));
+        assert_eq!(year.join("day3"), year_path(&root, DEFAULT_YEAR, "day3"));
+        assert_eq!(root.join("2024/day2"), year_path(&root, 2024, "day2"));
+        fs::remove_dir_all(&year).unwrap();
+        assert_eq!(root.join("day3"), year_path(&root, DEFAULT_YEAR, "day3"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
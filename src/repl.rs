@@ -0,0 +1,139 @@
+use std::any::Any;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::panic::{self, AssertUnwindSafe};
+
+use aoc23::solution::{Part, Querier, Solver};
+
+use crate::runner;
+
+// `repl` parses an input once and then answers questions about it, one
+// per line of stdin:
+//
+//     > hand 32T3K
+//     hand=32T3K kind=OnePair rank=1 of=5 bid=765
+//     > part 1
+//     6440
+//
+// Besides the day's own queries, `part <1|2>` solves a part on the
+// parsed model, `help` lists what can be asked and `quit`, like the end
+// of stdin, stops.
+
+pub struct Session<'a> {
+    pub solver: &'static dyn Solver,
+    pub querier: Option<&'static dyn Querier>,
+    pub model: &'a dyn Any,
+}
+
+// Run `f`, turning a panic into an error so that the session goes on.
+fn guarded<T>(f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|payload| Err(format!("panicked: {}", runner::panic_message(&*payload))))
+}
+
+impl Session<'_> {
+    fn help(&self) -> String {
+        let queries = self.querier.map_or(&[][..], |q| q.queries());
+        let mut help = String::from("queries:");
+        for (name, arg) in queries
+            .iter()
+            .chain(&[("part", "<1|2>"), ("help", ""), ("quit", "")])
+        {
+            help.push_str("\n    ");
+            help.push_str(format!("{} {}", name, arg).trim_end());
+        }
+        help
+    }
+
+    // The reply to one line, or None to stop.
+    pub fn answer(&self, line: &str) -> Option<Result<String, String>> {
+        let (name, arg) = line
+            .trim()
+            .split_once(char::is_whitespace)
+            .map_or((line.trim(), ""), |(name, arg)| (name, arg.trim()));
+        let reply = match name {
+            "quit" | "exit" => return None,
+            "help" => Ok(self.help()),
+            "part" => {
+                let part = match arg {
+                    "1" => Part::One,
+                    "2" => Part::Two,
+                    _ => return Some(Err(format!("invalid part '{}', expected 1 or 2", arg))),
+                };
+                guarded(|| {
+                    self.solver
+                        .solve(self.model, part)
+                        .map(|answer| answer.to_string())
+                        .map_err(|e| e.to_string())
+                })
+            }
+            _ => match self.querier {
+                Some(querier) => {
+                    guarded(|| querier.query(self.model, name, arg)).map(|step| step.to_string())
+                }
+                None => Err(format!("unknown query '{}', try help", name)),
+            },
+        };
+        Some(reply)
+    }
+}
+
+// Answer the lines of stdin until it ends or says quit. There is a
+// prompt only when someone is typing.
+pub fn run(session: &Session) -> io::Result<()> {
+    let stdin = io::stdin();
+    let prompt = stdin.is_terminal();
+    let mut lines = stdin.lock().lines();
+    loop {
+        if prompt {
+            print!("> ");
+            io::stdout().flush()?;
+        }
+        let Some(line) = lines.next() else {
+            return Ok(());
+        };
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match session.answer(&line) {
+            None => return Ok(()),
+            Some(Ok(reply)) => println!("{}", reply),
+            Some(Err(e)) => println!("error: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Session;
+    use aoc23::registry;
+
+    #[test]
+    fn test_answer() {
+        let solver = registry::find(2023, 7).unwrap();
+        let model = solver
+            .parse(include_str!("../examples/day7/example.txt"))
+            .unwrap();
+        let session = Session {
+            solver,
+            querier: registry::get(2023, 7).and_then(|d| d.querier),
+            model: model.as_ref(),
+        };
+        let answer = |line| session.answer(line).unwrap();
+
+        assert_eq!(
+            Ok("hand=32T3K kind=OnePair rank=1 of=5 bid=765".to_string()),
+            answer("hand 32T3K")
+        );
+        assert_eq!(
+            Ok("hand=AAAAA kind=FiveOf rank=6 of=5".to_string()),
+            answer("  hand   AAAAA ")
+        );
+        assert_eq!(Ok("6440".to_string()), answer("part 1"));
+        assert!(answer("hand 32T3").is_err());
+        assert!(answer("node AAA").is_err());
+        assert!(answer("part 3").is_err());
+        assert!(answer("help").unwrap().contains("hand <cards>"));
+        assert!(session.answer("quit").is_none());
+    }
+}
@@ -0,0 +1,68 @@
+use std::ops::Range;
+
+// A small seeded random number generator (splitmix64) for making up
+// puzzle inputs. The same seed always gives the same numbers, on any
+// platform, so a generated input can be reproduced from its seed.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    // A number in the range, which must not be empty.
+    pub fn range(&mut self, range: Range<u64>) -> u64 {
+        assert!(!range.is_empty(), "empty range {:?}", range);
+        range.start + self.next_u64() % (range.end - range.start)
+    }
+
+    pub fn index(&mut self, len: usize) -> usize {
+        self.range(0..len as u64) as usize
+    }
+
+    // True with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rng;
+
+    #[test]
+    fn test_deterministic() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        let xs: Vec<u64> = (0..100).map(|_| a.range(10..20)).collect();
+        assert_eq!(xs, (0..100).map(|_| b.range(10..20)).collect::<Vec<_>>());
+        assert!(xs.iter().all(|x| (10..20).contains(x)));
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+
+        let mut items: Vec<u32> = (0..10).collect();
+        a.shuffle(&mut items);
+        items.sort();
+        assert_eq!((0..10).collect::<Vec<_>>(), items);
+    }
+}
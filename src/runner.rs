@@ -0,0 +1,602 @@
+use std::any::Any;
+use std::collections::HashSet;
+use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use std::{env, fmt, fs, io};
+
+use crate::cli::{Days, RunArgs};
+use crate::config;
+use crate::format;
+use aoc23::answer::Answer;
+use aoc23::error::PuzzleError;
+use aoc23::params::Params;
+use aoc23::registry;
+use aoc23::solution::{Part, Solver, Step};
+use aoc23::toml::Document;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Failure {
+    Error(PuzzleError),
+    Panicked(String),
+    TimedOut(Duration),
+    // Not run because another part of the day timed out.
+    Skipped,
+}
+
+impl Failure {
+    // What went wrong, for the diagnostics below the results.
+    pub fn message(&self) -> String {
+        match self {
+            Failure::Error(e) => e.to_string(),
+            Failure::Panicked(msg) => format!("panicked: {}", msg),
+            Failure::TimedOut(timeout) => format!("no answer after {:.2?}", timeout),
+            Failure::Skipped => "not run, another part of the day timed out".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Error(_) => write!(f, "ERROR"),
+            Failure::Panicked(_) => write!(f, "PANIC"),
+            Failure::TimedOut(_) => write!(f, "TIMEOUT"),
+            Failure::Skipped => write!(f, "SKIPPED"),
+        }
+    }
+}
+
+pub struct Outcome {
+    pub year: u32,
+    pub day: u32,
+    pub part: Part,
+    pub input: String,
+    pub input_hash: u64,
+    pub answer: Result<Answer, Failure>,
+    pub elapsed: Duration,
+}
+
+// Directory holding the <year>/day<n> puzzle inputs, overridable
+// through the AOC_DATA_DIR environment variable.
+pub fn data_dir() -> PathBuf {
+    env::var_os("AOC_DATA_DIR")
+        .map(PathBuf::from)
+        .or_else(|| config::get().data_dir.clone())
+        .unwrap_or_else(|| PathBuf::from("data"))
+}
+
+// One of the inputs, answers and attempts of a year, by file name.
+pub fn year_data_path(year: u32, name: &str) -> PathBuf {
+    registry::year_path(&data_dir(), year, name)
+}
+
+pub fn data_path(year: u32, day: u32) -> String {
+    year_data_path(year, &format!("day{}", day))
+        .to_string_lossy()
+        .into_owned()
+}
+
+// Read a puzzle input, where a path of "-" stands for stdin.
+pub fn read_input(path: &str) -> io::Result<String> {
+    if path == "-" {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        Ok(input)
+    } else {
+        fs::read_to_string(path)
+    }
+}
+
+pub fn examples_dir(year: u32, day: u32) -> String {
+    registry::year_path(Path::new("examples"), year, &format!("day{}", day))
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn example_paths(year: u32, day: u32) -> io::Result<Vec<String>> {
+    let dir = examples_dir(year, day);
+    let mut paths = vec![];
+    for entry in fs::read_dir(&dir).map_err(|e| with_path(e, &dir))? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "txt") {
+            paths.push(path.to_string_lossy().into_owned());
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+fn input_paths(day: u32, args: &RunArgs) -> io::Result<Vec<String>> {
+    if args.example {
+        example_paths(args.year, day)
+    } else if let Some(input) = &args.input {
+        Ok(vec![input.clone()])
+    } else {
+        Ok(vec![data_path(args.year, day)])
+    }
+}
+
+fn with_path(e: io::Error, path: &str) -> io::Error {
+    io::Error::new(e.kind(), format!("{}: {}", path, e))
+}
+
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown cause".to_string()
+    }
+}
+
+type PanicHook = Box<dyn Fn(&panic::PanicHookInfo<'_>) + Sync + Send>;
+
+// Keeps the panic hook from printing the message and backtrace of a
+// worker that panics, since its part reports the panic with the
+// results. Panics on other threads still go to the previous hook,
+// which is put back when the guard is dropped.
+pub struct QuietWorkers {
+    previous: Option<Arc<PanicHook>>,
+}
+
+impl QuietWorkers {
+    pub fn install() -> Self {
+        let previous = Arc::new(panic::take_hook());
+        let hook = previous.clone();
+        panic::set_hook(Box::new(move |info| {
+            let name = thread::current().name().map(str::to_string);
+            if !name.is_some_and(|name| name.starts_with(WORKER_PREFIX)) {
+                hook(info);
+            }
+        }));
+        QuietWorkers {
+            previous: Some(previous),
+        }
+    }
+}
+
+impl Drop for QuietWorkers {
+    fn drop(&mut self) {
+        // Dropping the quiet hook releases its hold on the previous one.
+        drop(panic::take_hook());
+        if let Some(previous) = self.previous.take() {
+            match Arc::try_unwrap(previous) {
+                Ok(previous) => panic::set_hook(previous),
+                Err(previous) => panic::set_hook(Box::new(move |info| previous(info))),
+            }
+        }
+    }
+}
+
+const WORKER_PREFIX: &str = "solve-";
+
+// Parse the input with the day's parameters and solve one part on a
+// worker thread, waiting at most `timeout` for it. A worker that times out cannot be stopped; it
+// is left behind and dies with the process.
+pub fn solve_part(
+    solver: &'static dyn Solver,
+    input: Arc<str>,
+    params: Params,
+    part: Part,
+    timeout: Duration,
+) -> (Result<Answer, Failure>, Duration) {
+    let (tx, rx) = mpsc::channel();
+    thread::Builder::new()
+        .name(format!("{}day{}-part{}", WORKER_PREFIX, solver.day(), part))
+        .spawn(move || {
+            // The part's time includes parsing the input.
+            let start = Instant::now();
+            let answer = panic::catch_unwind(AssertUnwindSafe(|| {
+                let model = solver.parse_with(&input, &params)?;
+                solver.solve(model.as_ref(), part)
+            }));
+            let elapsed = start.elapsed();
+            let answer = match answer {
+                Ok(Ok(answer)) => Ok(answer),
+                Ok(Err(e)) => Err(Failure::Error(e)),
+                Err(payload) => Err(Failure::Panicked(panic_message(payload.as_ref()))),
+            };
+            // The runner may have stopped waiting already.
+            let _ = tx.send((answer, elapsed));
+        })
+        .expect("failed to spawn a worker thread");
+
+    match rx.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => (Err(Failure::TimedOut(timeout)), timeout),
+        Err(RecvTimeoutError::Disconnected) => (
+            Err(Failure::Panicked("worker exited early".to_string())),
+            Duration::ZERO,
+        ),
+    }
+}
+
+// Apply `f` to every task on up to `threads` workers, which take the
+// next task as they finish one. The results come back in task order.
+fn parallel_map<T, R, F>(tasks: &[T], threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, R)> = thread::scope(|s| {
+        let workers: Vec<_> = (0..threads.clamp(1, tasks.len().max(1)))
+            .map(|_| {
+                s.spawn(|| {
+                    let mut done = vec![];
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        match tasks.get(i) {
+                            Some(task) => done.push((i, f(task))),
+                            None => return done,
+                        }
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|w| w.join().expect("runner worker panicked"))
+            .collect()
+    });
+    results.sort_by_key(|&(i, _)| i);
+    results.into_iter().map(|(_, r)| r).collect()
+}
+
+pub fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+pub struct Job {
+    pub solver: &'static dyn Solver,
+    pub path: String,
+    pub input: Arc<str>,
+    // The selected parts to solve on this input.
+    pub parts: Vec<Part>,
+    // The day's parameters from the config file.
+    pub params: Params,
+}
+
+// The parts of `selected` an example has an answer for in the .toml
+// next to it. An example without one is solved on every part.
+fn example_parts(path: &str, selected: Vec<Part>) -> io::Result<Vec<Part>> {
+    let sidecar = Path::new(path).with_extension("toml");
+    let text = match fs::read_to_string(&sidecar) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(selected),
+        Err(e) => return Err(with_path(e, &sidecar.to_string_lossy())),
+    };
+    let doc = Document::parse(&text).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", sidecar.display(), e),
+        )
+    })?;
+    Ok(selected
+        .into_iter()
+        .filter(|part| doc.get("", &format!("part{}", part)).is_some())
+        .collect())
+}
+
+// The inputs the arguments select, one job per day and input file.
+// With --all, days without an input are skipped. With --example, each
+// example is solved only on the parts its .toml gives an answer for.
+pub fn jobs(args: &RunArgs) -> io::Result<Vec<Job>> {
+    let solvers = match args.days {
+        Days::All => registry::days(args.year),
+        Days::One(day) => match registry::find(args.year, day) {
+            Some(solver) => vec![solver],
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("day {} of {} is not implemented", day, args.year),
+                ))
+            }
+        },
+    };
+
+    let mut jobs = vec![];
+    for solver in solvers {
+        let paths = match input_paths(solver.day(), args) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound && args.days == Days::All => {
+                eprintln!("skipping day {}: no examples", solver.day());
+                continue;
+            }
+            paths => paths?,
+        };
+
+        for path in paths {
+            let input = match read_input(&path) {
+                Ok(input) => input,
+                Err(e) if e.kind() == io::ErrorKind::NotFound && args.days == Days::All => {
+                    eprintln!("skipping day {}: no input at {}", solver.day(), path);
+                    continue;
+                }
+                Err(e) => return Err(with_path(e, &path)),
+            };
+            let parts = match args.example {
+                true => example_parts(&path, args.parts())?,
+                false => args.parts(),
+            };
+            if parts.is_empty() {
+                continue;
+            }
+            jobs.push(Job {
+                solver,
+                path,
+                input: Arc::from(input),
+                parts,
+                params: config::get().params(args.year, solver.day()),
+            });
+        }
+    }
+    Ok(jobs)
+}
+
+pub struct Report {
+    pub outcomes: Vec<Outcome>,
+    // Time the whole run took, as opposed to the sum of the parts.
+    pub wall: Duration,
+    // With --explain, the trace of every part, in the order of the
+    // outcomes.
+    pub traces: Vec<Trace>,
+}
+
+pub struct Trace {
+    pub day: u32,
+    pub part: Part,
+    pub input: String,
+    pub steps: Result<Vec<Step>, String>,
+}
+
+// Trace how the day solves the part. This runs after the timed solve,
+// on the calling thread.
+fn trace(job: &Job, part: Part) -> Trace {
+    let day = job.solver.day();
+    let steps = match registry::get(job.solver.year(), day).and_then(|d| d.explainer) {
+        None => Err(format!("day {} has no trace", day)),
+        Some(explainer) => job
+            .solver
+            .parse_with(&job.input, &job.params)
+            .map_err(|e| e.to_string())
+            .and_then(|model| {
+                panic::catch_unwind(AssertUnwindSafe(|| explainer.explain(model.as_ref(), part)))
+                    .map_err(|payload| format!("panicked: {}", panic_message(payload.as_ref())))
+            }),
+    };
+    Trace {
+        day,
+        part,
+        input: job.path.clone(),
+        steps,
+    }
+}
+
+// Solve every selected part of every input, independently of each
+// other, on `args.threads` threads. A worker that times out cannot be
+// stopped and keeps its CPU until the process exits, so once a part of
+// a day times out, the parts of that day not started yet are skipped.
+pub fn run(args: &RunArgs) -> io::Result<Report> {
+    let jobs = jobs(args)?;
+    let tasks: Vec<(&Job, Part)> = jobs
+        .iter()
+        .flat_map(|job| job.parts.iter().map(move |&part| (job, part)))
+        .collect();
+    let threads = args.threads.unwrap_or_else(default_threads);
+
+    let timed_out = Mutex::new(HashSet::new());
+    let quiet = QuietWorkers::install();
+    let start = Instant::now();
+    let outcomes = parallel_map(&tasks, threads, |&(job, part)| {
+        let day = (job.solver.year(), job.solver.day());
+        let (answer, elapsed) = match timed_out.lock().unwrap().contains(&day) {
+            true => (Err(Failure::Skipped), Duration::ZERO),
+            false => solve_part(
+                job.solver,
+                job.input.clone(),
+                job.params.clone(),
+                part,
+                args.timeout,
+            ),
+        };
+        if let Err(Failure::TimedOut(_)) = answer {
+            timed_out.lock().unwrap().insert(day);
+        }
+        Outcome {
+            year: job.solver.year(),
+            day: job.solver.day(),
+            part,
+            input: job.path.clone(),
+            input_hash: format::input_hash(&job.input),
+            answer,
+            elapsed,
+        }
+    });
+    let wall = start.elapsed();
+    drop(quiet);
+    let traces = match args.explain {
+        true => tasks.iter().map(|&(job, part)| trace(job, part)).collect(),
+        false => vec![],
+    };
+    Ok(Report {
+        outcomes,
+        wall,
+        traces,
+    })
+}
+
+pub fn print_table(report: &Report) {
+    let outcomes = &report.outcomes;
+    let answer = |o: &Outcome| match &o.answer {
+        Ok(a) => a.to_string(),
+        Err(e) => format!("<{}>", e),
+    };
+    let width = |name: &str, col: &dyn Fn(&Outcome) -> usize| {
+        outcomes.iter().map(col).chain([name.len()]).max().unwrap()
+    };
+    let input_w = width("input", &|o| o.input.len());
+    let answer_w = width("answer", &|o| answer(o).len());
+
+    println!(
+        "{:>3}  {:>4}  {:<input_w$}  {:<answer_w$}  {:>10}",
+        "day", "part", "input", "answer", "time"
+    );
+    for o in outcomes {
+        println!(
+            "{:>3}  {:>4}  {:<input_w$}  {:<answer_w$}  {:>10}",
+            o.day,
+            o.part,
+            o.input,
+            answer(o),
+            format!("{:.2?}", o.elapsed),
+        );
+    }
+    let total: Duration = outcomes.iter().map(|o| o.elapsed).sum();
+    println!(
+        "total: {:.2?} wall, {:.2?} summed over parts",
+        report.wall, total
+    );
+}
+
+pub fn print_traces(report: &Report) {
+    for trace in &report.traces {
+        println!();
+        println!("day {} part {} on {}:", trace.day, trace.part, trace.input);
+        match &trace.steps {
+            Ok(steps) => steps.iter().for_each(|step| println!("  {}", step)),
+            Err(e) => println!("  {}", e),
+        }
+    }
+}
+
+// Print the diagnostics of every failed part, once per distinct
+// error, and tell whether there were any.
+pub fn report_failures(outcomes: &[Outcome]) -> bool {
+    let mut reported: Vec<&PuzzleError> = vec![];
+    let mut failed = false;
+    for o in outcomes {
+        match &o.answer {
+            Ok(_) => continue,
+            Err(Failure::Error(e)) if !reported.contains(&e) => {
+                eprintln!("{}: {}", o.input, e);
+                reported.push(e);
+            }
+            Err(Failure::Error(_)) => (),
+            Err(failure) => {
+                eprintln!(
+                    "{}: day {} part {} {}",
+                    o.input,
+                    o.day,
+                    o.part,
+                    failure.message()
+                );
+            }
+        }
+        failed = true;
+    }
+    failed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{example_parts, parallel_map, run, solve_part, Failure};
+    use crate::cli::{Days, RunArgs};
+    use crate::format::Format;
+    use aoc23::params::Params;
+    use aoc23::registry;
+    use aoc23::solution::Part;
+    use std::fs;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_isolated_failures() {
+        let day7 = registry::find(2023, 7).unwrap();
+        let input: Arc<str> = Arc::from("32T3K 765\n");
+        let timeout = Duration::from_secs(5);
+        assert!(
+            solve_part(day7, input.clone(), Params::new(), Part::One, timeout)
+                .0
+                .is_ok()
+        );
+        assert!(matches!(
+            solve_part(day7, input, Params::new(), Part::Two, timeout).0,
+            Err(Failure::Panicked(_))
+        ));
+
+        // ZZZ is never reached from AAA.
+        let day8 = registry::find(2023, 8).unwrap();
+        let input = Arc::from("L\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)\n");
+        let timeout = Duration::from_millis(100);
+        assert_eq!(
+            Err(Failure::TimedOut(timeout)),
+            solve_part(day8, input, Params::new(), Part::One, timeout).0
+        );
+    }
+
+    #[test]
+    fn test_parallel_map_order() {
+        // Earlier tasks take longer, so they finish last.
+        let tasks: Vec<u64> = (0..8).collect();
+        let results = parallel_map(&tasks, 4, |&n| {
+            thread::sleep(Duration::from_millis(2 * (8 - n)));
+            n * n
+        });
+        assert_eq!(vec![0, 1, 4, 9, 16, 25, 36, 49], results);
+        assert!(parallel_map(&[] as &[u64], 4, |&n| n).is_empty());
+    }
+
+    #[test]
+    fn test_example_parts() {
+        // The part 2 example of day 1 has no part 1 answer.
+        assert_eq!(
+            vec![Part::Two],
+            example_parts("examples/day1/part_2.txt", Part::BOTH.to_vec()).unwrap()
+        );
+        assert_eq!(
+            Part::BOTH.to_vec(),
+            example_parts("examples/day1/example.txt", Part::BOTH.to_vec()).unwrap()
+        );
+        assert!(example_parts("examples/day1/part_2.txt", vec![Part::One])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_skip_after_timeout() {
+        // Neither part of day 8 ever gets from AAA to a Z node.
+        let path = std::env::temp_dir().join(format!("aoc23-skip-{}", std::process::id()));
+        fs::write(
+            &path,
+            "L\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)\n",
+        )
+        .unwrap();
+        let args = RunArgs {
+            year: 2023,
+            days: Days::One(8),
+            part: None,
+            input: Some(path.to_string_lossy().into_owned()),
+            example: false,
+            format: Format::Text,
+            timeout: Duration::from_millis(100),
+            threads: Some(1),
+            explain: false,
+        };
+        let outcomes = run(&args).unwrap().outcomes;
+        fs::remove_file(&path).unwrap();
+        let answers: Vec<_> = outcomes.into_iter().map(|o| o.answer).collect();
+        assert_eq!(
+            vec![Err(Failure::TimedOut(args.timeout)), Err(Failure::Skipped)],
+            answers
+        );
+    }
+}
@@ -0,0 +1,353 @@
+use std::path::Path;
+use std::process::Command;
+use std::{fs, io};
+
+use aoc23::registry::DEFAULT_YEAR;
+
+use crate::runner;
+
+// `new-day` writes a src/day<n>.rs to start from, with an empty input
+// and example (and example answers) to paste into, and lists the day
+// in lib.rs and the registry. Days of years other than the default one
+// go in a module of their year, src/y<year>/day<n>.rs.
+
+const TEMPLATE: &str = r#"use crate::error::PuzzleError;
+use crate::parse;
+use crate::solution::{Lint, Solution};
+
+pub struct Day{N};
+
+impl Solution for Day{N} {
+    const YEAR: u32 = {YEAR};
+    const DAY: u32 = {N};
+    const TITLE: &'static str = "{TITLE}";
+
+    type Model = Vec<String>;
+    type PartOne = u64;
+    type PartTwo = u64;
+
+    fn parse(input: &str) -> Result<Self::Model, PuzzleError> {
+        parse(input)
+    }
+
+    fn part_one(lines: &Self::Model) -> Result<u64, PuzzleError> {
+        part_one(lines)
+    }
+
+    fn part_two(lines: &Self::Model) -> Result<u64, PuzzleError> {
+        part_two(lines)
+    }
+}
+
+impl Lint for Day{N} {}
+
+pub fn parse(input: &str) -> Result<Vec<String>, PuzzleError> {
+    Ok(parse::lines(input).map(|l| l.text.to_string()).collect())
+}
+
+pub fn part_one(_lines: &[String]) -> Result<u64, PuzzleError> {
+    todo!()
+}
+
+pub fn part_two(_lines: &[String]) -> Result<u64, PuzzleError> {
+    todo!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, part_one, part_two};
+
+    #[test]
+    #[ignore = "paste the example and its answers"]
+    fn test_puzzle_example() {
+        let input = include_str!("{EXAMPLE}");
+        let lines = parse(input).unwrap();
+        assert_eq!(Ok(0), part_one(&lines));
+        assert_eq!(Ok(0), part_two(&lines));
+    }
+}
+"#;
+
+// The answers tests/examples.rs checks the example against.
+const EXAMPLE_ANSWERS: &str = "# part1 = 0\n# part2 = 0\n";
+
+pub fn module_path(year: u32, day: u32) -> String {
+    if year == DEFAULT_YEAR {
+        format!("src/day{}.rs", day)
+    } else {
+        format!("src/y{}/day{}.rs", year, day)
+    }
+}
+
+// The source of a new day, whose example is at `example`, relative to
+// the crate root.
+pub fn source(year: u32, day: u32, title: &str, example: &str) -> String {
+    let title = title.replace('\\', "\\\\").replace('"', "\\\"");
+    let up = if year == DEFAULT_YEAR {
+        "../"
+    } else {
+        "../../"
+    };
+    TEMPLATE
+        .replace("{YEAR}", &year.to_string())
+        .replace("{N}", &day.to_string())
+        .replace("{EXAMPLE}", &format!("{}{}", up, example))
+        .replace("{TITLE}", &title)
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// Add `pub mod <module>;` to a module list: a day after the last day,
+// anything else after the last module. An empty file gets a list of
+// its own.
+pub fn register_module(list: &str, module: &str) -> io::Result<String> {
+    let decl = format!("pub mod {};", module);
+    if list.lines().any(|l| l.trim() == decl) {
+        return Err(invalid(format!("{} is already declared", module)));
+    }
+    if list.trim().is_empty() {
+        return Ok(format!("{}\n", decl));
+    }
+    let prefix = if module.starts_with("day") {
+        "pub mod day"
+    } else {
+        "pub mod "
+    };
+    let last = list
+        .lines()
+        .rfind(|l| l.starts_with(prefix))
+        .ok_or_else(|| invalid("no modules to add to".to_string()))?;
+    let at = list.find(last).unwrap() + last.len();
+    Ok(format!("{}\n{}{}", &list[..at], decl, &list[at..]))
+}
+
+// The year and day of a DAYS entry, `day!(day<n>::Day<n>, ...)` for the
+// default year and `day!(y<year>::day<n>::Day<n>, ...)` for the others.
+fn entry_of(entry: &str) -> Option<(u32, u32)> {
+    let path = entry.trim().strip_prefix("day!(")?;
+    let (year, path) = match path.strip_prefix('y') {
+        Some(rest) => {
+            let (year, rest) = rest.split_once("::")?;
+            (year.parse().ok()?, rest)
+        }
+        None => (DEFAULT_YEAR, path),
+    };
+    Some((
+        year,
+        path.split_once("::")?.0.strip_prefix("day")?.parse().ok()?,
+    ))
+}
+
+// Where a module imported by registry.rs sorts: the days of the
+// default year in day order, then the other years.
+fn import_key(item: &str) -> Option<(u32, u32)> {
+    let item = item.trim();
+    match item.strip_prefix("day") {
+        Some(day) => Some((0, day.parse().ok()?)),
+        None => Some((item.strip_prefix('y')?.parse().ok()?, 0)),
+    }
+}
+
+// Add the day to the imports and the DAYS list of registry.rs, keeping
+// both in order. The day starts out without optional capabilities.
+pub fn register_solver(registry: &str, year: u32, day: u32) -> io::Result<String> {
+    let missing = |what: &str| invalid(format!("no {} in registry.rs", what));
+
+    let start = registry
+        .find("use crate::{")
+        .ok_or_else(|| missing("day imports"))?
+        + "use crate::{".len();
+    let end = start
+        + registry[start..]
+            .find('}')
+            .ok_or_else(|| missing("day imports"))?;
+    let mut imports: Vec<(u32, u32)> = registry[start..end]
+        .split(',')
+        .filter_map(import_key)
+        .collect();
+
+    let list_start = registry
+        .find("static DAYS")
+        .and_then(|i| registry[i..].find("= &[").map(|j| i + j + "= &[".len()))
+        .ok_or_else(|| missing("DAYS list"))?;
+    let list_end = list_start
+        + registry[list_start..]
+            .find("];")
+            .ok_or_else(|| missing("DAYS list"))?;
+    let mut entries: Vec<((u32, u32), String)> = registry[list_start..list_end]
+        .lines()
+        .filter_map(|line| Some((entry_of(line)?, line.trim().to_string())))
+        .collect();
+    if entries.iter().any(|(key, _)| *key == (year, day)) {
+        return Err(invalid(format!(
+            "registry.rs already lists day{} of {}",
+            day, year
+        )));
+    }
+    let entry = match year {
+        DEFAULT_YEAR => format!("day!(day{0}::Day{0}),", day),
+        year => format!("day!(y{1}::day{0}::Day{0}),", day, year),
+    };
+    entries.push(((year, day), entry));
+    entries.sort();
+
+    let import = if year == DEFAULT_YEAR {
+        (0, day)
+    } else {
+        (year, 0)
+    };
+    if !imports.contains(&import) {
+        imports.push(import);
+        imports.sort();
+    }
+    let imports = imports
+        .iter()
+        .map(|&(y, d)| match y {
+            0 => format!("day{}", d),
+            y => format!("y{}", y),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let days: String = entries
+        .iter()
+        .map(|(_, entry)| format!("\n    {}", entry))
+        .collect();
+
+    Ok(format!(
+        "{}{}{}{}\n{}",
+        &registry[..start],
+        imports,
+        &registry[end..list_start],
+        days,
+        &registry[list_end..],
+    ))
+}
+
+fn create(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, contents)
+}
+
+// Create the files of a new day and register it, returning the paths
+// written. Nothing is touched when the day already exists.
+pub fn new_day(year: u32, day: u32, title: &str) -> io::Result<Vec<String>> {
+    let module = module_path(year, day);
+    let data = runner::data_path(year, day);
+    let example = format!("{}/example.txt", runner::examples_dir(year, day));
+    let answers = format!("{}/example.toml", runner::examples_dir(year, day));
+    for path in [&module, &data, &example, &answers] {
+        if Path::new(path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", path),
+            ));
+        }
+    }
+    // A day of the default year is a module of the crate, any other
+    // one a module of its year, which is itself one of the crate.
+    let lib = fs::read_to_string("src/lib.rs")?;
+    let mut edits = vec![];
+    if year == DEFAULT_YEAR {
+        edits.push((
+            "src/lib.rs".to_string(),
+            register_module(&lib, &format!("day{}", day))?,
+        ));
+    } else {
+        let parent = format!("src/y{}/mod.rs", year);
+        let list = match fs::read_to_string(&parent) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            list => list?,
+        };
+        edits.push((parent, register_module(&list, &format!("day{}", day))?));
+        let year_module = format!("y{}", year);
+        if !lib
+            .lines()
+            .any(|l| l.trim() == format!("pub mod {};", year_module))
+        {
+            edits.push((
+                "src/lib.rs".to_string(),
+                register_module(&lib, &year_module)?,
+            ));
+        }
+    }
+    let registry = register_solver(&fs::read_to_string("src/registry.rs")?, year, day)?;
+    edits.push(("src/registry.rs".to_string(), registry));
+
+    create(Path::new(&module), &source(year, day, title, &example))?;
+    create(Path::new(&data), "")?;
+    create(Path::new(&example), "")?;
+    create(Path::new(&answers), EXAMPLE_ANSWERS)?;
+    for (path, contents) in &edits {
+        create(Path::new(path), contents)?;
+    }
+
+    // Leave the layout of the edited files to rustfmt when it is around.
+    let _ = Command::new("rustfmt")
+        .args(["--edition", "2021", &module])
+        .args(edits.iter().map(|(path, _)| path))
+        .status();
+
+    let mut written = vec![module, data, example, answers];
+    written.extend(edits.into_iter().map(|(path, _)| path));
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{register_module, register_solver, source};
+
+    #[test]
+    fn test_register() {
+        let lib = "pub mod answer;\npub mod day1;\npub mod day2;\npub mod error;\n";
+        assert_eq!(
+            "pub mod answer;\npub mod day1;\npub mod day2;\npub mod day9;\npub mod error;\n",
+            register_module(lib, "day9").unwrap()
+        );
+        assert!(register_module(lib, "day2").is_err());
+        assert_eq!(
+            "pub mod answer;\npub mod day1;\npub mod day2;\npub mod error;\npub mod y2024;\n",
+            register_module(lib, "y2024").unwrap()
+        );
+        assert_eq!("pub mod day3;\n", register_module("", "day3").unwrap());
+
+        let registry = "use crate::{day1, day3};\n\n\
+                        static DAYS: &[Day] = &[\n    day!(day1::Day1, generator),\n    day!(day3::Day3),\n];\n\n\
+                        pub fn all() {}\n";
+        assert_eq!(
+            "use crate::{day1, day2, day3};\n\n\
+             static DAYS: &[Day] = &[\n    day!(day1::Day1, generator),\n    day!(day2::Day2),\n    day!(day3::Day3),\n];\n\n\
+             pub fn all() {}\n",
+            register_solver(registry, 2023, 2).unwrap()
+        );
+        assert!(register_solver(registry, 2023, 3).is_err());
+
+        let registry = register_solver(registry, 2024, 5).unwrap();
+        assert_eq!(
+            "use crate::{day1, day3, y2024};\n\n\
+             static DAYS: &[Day] = &[\n    day!(day1::Day1, generator),\n    day!(day3::Day3),\n    day!(y2024::day5::Day5),\n];\n\n\
+             pub fn all() {}\n",
+            registry
+        );
+        assert!(register_solver(&registry, 2024, 5).is_err());
+        assert!(register_solver(&registry, 2024, 1)
+            .unwrap()
+            .contains("day!(y2024::day1::Day1),\n    day!(y2024::day5::Day5),"));
+    }
+
+    #[test]
+    fn test_source() {
+        let src = source(2023, 12, "Hot \"Springs\"", "examples/day12/example.txt");
+        assert!(src.contains("pub struct Day12;"));
+        assert!(src.contains("const DAY: u32 = 12;"));
+        assert!(src.contains(r#"const TITLE: &'static str = "Hot \"Springs\"";"#));
+        assert!(src.contains("const YEAR: u32 = 2023;"));
+        assert!(src.contains("\"../examples/day12/example.txt\""));
+
+        let src = source(2024, 1, "Day 1", "examples/2024/day1/example.txt");
+        assert!(src.contains("\"../../examples/2024/day1/example.txt\""));
+    }
+}
@@ -0,0 +1,263 @@
+use std::any::Any;
+use std::fmt::{self, Display};
+
+use crate::answer::Answer;
+use crate::error::PuzzleError;
+use crate::params::Params;
+use crate::rng::Rng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const BOTH: [Part; 2] = [Part::One, Part::Two];
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => f.pad("1"),
+            Part::Two => f.pad("2"),
+        }
+    }
+}
+
+// A day's puzzle: how to turn the raw input into a model, and how to
+// answer both parts from that model.
+pub trait Solution {
+    const YEAR: u32;
+    const DAY: u32;
+    const TITLE: &'static str;
+    // Names of the parameters `parse_with` reads.
+    const PARAMS: &'static [&'static str] = &[];
+
+    type Model: 'static;
+    type PartOne: Into<Answer>;
+    type PartTwo: Into<Answer>;
+
+    fn parse(input: &str) -> Result<Self::Model, PuzzleError>;
+    // Parse with the parameters the config overrides. Days without
+    // parameters keep the default, which ignores them.
+    fn parse_with(input: &str, _params: &Params) -> Result<Self::Model, PuzzleError> {
+        Self::parse(input)
+    }
+    fn part_one(model: &Self::Model) -> Result<Self::PartOne, PuzzleError>;
+    fn part_two(model: &Self::Model) -> Result<Self::PartTwo, PuzzleError>;
+}
+
+// Object safe view of a Solution, so that days with different model
+// and answer types can sit side by side in the registry. Each optional
+// capability below has a view of its own, which the registry keeps
+// next to the solver in the day's entry.
+pub trait Solver: Sync {
+    fn year(&self) -> u32;
+    fn day(&self) -> u32;
+    fn title(&self) -> &'static str;
+    fn params(&self) -> &'static [&'static str];
+    fn parse(&self, input: &str) -> Result<Box<dyn Any>, PuzzleError>;
+    fn parse_with(&self, input: &str, params: &Params) -> Result<Box<dyn Any>, PuzzleError>;
+    fn solve(&self, model: &dyn Any, part: Part) -> Result<Answer, PuzzleError>;
+}
+
+impl<S: Solution + Sync> Solver for S {
+    fn year(&self) -> u32 {
+        S::YEAR
+    }
+
+    fn day(&self) -> u32 {
+        S::DAY
+    }
+
+    fn title(&self) -> &'static str {
+        S::TITLE
+    }
+
+    fn params(&self) -> &'static [&'static str] {
+        S::PARAMS
+    }
+
+    fn parse(&self, input: &str) -> Result<Box<dyn Any>, PuzzleError> {
+        match S::parse(input) {
+            Ok(model) => Ok(Box::new(model)),
+            Err(e) => Err(e.in_day(S::DAY)),
+        }
+    }
+
+    fn parse_with(&self, input: &str, params: &Params) -> Result<Box<dyn Any>, PuzzleError> {
+        match S::parse_with(input, params) {
+            Ok(model) => Ok(Box::new(model)),
+            Err(e) => Err(e.in_day(S::DAY)),
+        }
+    }
+
+    fn solve(&self, model: &dyn Any, part: Part) -> Result<Answer, PuzzleError> {
+        let model = model
+            .downcast_ref::<S::Model>()
+            .expect("model parsed by another day");
+        let answer = match part {
+            Part::One => S::part_one(model).map(Into::into),
+            Part::Two => S::part_two(model).map(Into::into),
+        };
+        answer.map_err(|e| e.in_day(S::DAY))
+    }
+}
+
+// Makes up random but well-formed inputs for a day, for stress tests
+// and benchmarks. `size` counts whatever the day's input is a list of:
+// lines, games, cards, hands, or rows and columns of a schematic.
+pub trait Generator: Solution {
+    fn generate(rng: &mut Rng, size: usize) -> String;
+}
+
+pub trait InputGenerator: Sync {
+    fn input(&self, seed: u64, size: usize) -> String;
+}
+
+impl<G: Generator + Sync> InputGenerator for G {
+    fn input(&self, seed: u64, size: usize) -> String {
+        G::generate(&mut Rng::new(seed), size)
+    }
+}
+
+// Slow but plainly correct answers for a day, to check the real
+// solution against on generated inputs. A part gives None when it has
+// no reference, or when the model is too big to solve the slow way.
+pub trait Reference: Solution {
+    fn naive_part_one(_model: &Self::Model) -> Option<Self::PartOne> {
+        None
+    }
+
+    fn naive_part_two(_model: &Self::Model) -> Option<Self::PartTwo> {
+        None
+    }
+}
+
+pub trait ReferenceSolver: Sync {
+    fn naive(&self, model: &dyn Any, part: Part) -> Option<Answer>;
+}
+
+impl<R: Reference + Sync> ReferenceSolver for R {
+    fn naive(&self, model: &dyn Any, part: Part) -> Option<Answer> {
+        let model = model
+            .downcast_ref::<R::Model>()
+            .expect("model parsed by another day");
+        match part {
+            Part::One => R::naive_part_one(model).map(Into::into),
+            Part::Two => R::naive_part_two(model).map(Into::into),
+        }
+    }
+}
+
+// Checks an input without solving it. Unlike `parse`, which stops at
+// the first problem, a lint reports every one it finds. Every day
+// implements it; days without checks of their own keep the default,
+// which reports the parse error, if any.
+pub trait Lint: Solution {
+    fn lint(input: &str) -> Vec<PuzzleError> {
+        Self::parse(input).err().into_iter().collect()
+    }
+}
+
+pub trait Linter: Sync {
+    fn lint(&self, input: &str) -> Vec<PuzzleError>;
+}
+
+impl<L: Lint + Sync> Linter for L {
+    // The problems in input order, problems with the input as a whole
+    // first. An input that lints clean always parses.
+    fn lint(&self, input: &str) -> Vec<PuzzleError> {
+        let mut errors = L::lint(input);
+        if errors.is_empty() {
+            errors.extend(L::parse(input).err());
+        }
+        errors.sort_by_key(|e| e.span.as_ref().map(|s| (s.line, s.column)));
+        errors.into_iter().map(|e| e.in_day(L::DAY)).collect()
+    }
+}
+
+// One step of a solver's reasoning, as named values in the order they
+// were added, e.g. `game=3 draw=2 red=20 limit=12`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub fields: Vec<(&'static str, String)>,
+}
+
+impl Step {
+    pub fn new() -> Self {
+        Step::default()
+    }
+
+    pub fn with(mut self, name: &'static str, value: impl Display) -> Self {
+        self.fields.push((name, value.to_string()));
+        self
+    }
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (name, value)) in self.fields.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            if value.is_empty() || value.contains(' ') {
+                write!(f, "{}={:?}", name, value)?;
+            } else {
+                write!(f, "{}={}", name, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// A trace of how a day reaches its answer, for finding the line behind
+// a rejected one.
+pub trait Explain: Solution {
+    fn explain(model: &Self::Model, part: Part) -> Vec<Step>;
+}
+
+pub trait Explainer: Sync {
+    fn explain(&self, model: &dyn Any, part: Part) -> Vec<Step>;
+}
+
+impl<E: Explain + Sync> Explainer for E {
+    fn explain(&self, model: &dyn Any, part: Part) -> Vec<Step> {
+        let model = model
+            .downcast_ref::<E::Model>()
+            .expect("model parsed by another day");
+        E::explain(model, part)
+    }
+}
+
+// Questions about a parsed input, answered by the repl without solving
+// the puzzle, e.g. where day 5 takes a single seed. QUERIES lists the
+// name of each with what it takes, for the repl's help.
+pub trait Query: Solution {
+    const QUERIES: &'static [(&'static str, &'static str)];
+
+    // Answer the query `name`, one of QUERIES, about `arg`.
+    fn query(model: &Self::Model, name: &str, arg: &str) -> Result<Step, String>;
+}
+
+pub trait Querier: Sync {
+    fn queries(&self) -> &'static [(&'static str, &'static str)];
+    fn query(&self, model: &dyn Any, name: &str, arg: &str) -> Result<Step, String>;
+}
+
+impl<Q: Query + Sync> Querier for Q {
+    fn queries(&self) -> &'static [(&'static str, &'static str)] {
+        Q::QUERIES
+    }
+
+    fn query(&self, model: &dyn Any, name: &str, arg: &str) -> Result<Step, String> {
+        if !Q::QUERIES.iter().any(|(query, _)| *query == name) {
+            return Err(format!("day {} has no query '{}'", Q::DAY, name));
+        }
+        let model = model
+            .downcast_ref::<Q::Model>()
+            .expect("model parsed by another day");
+        Q::query(model, name, arg)
+    }
+}
@@ -0,0 +1,466 @@
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use aoc23::answer::Answer;
+use aoc23::solution::Part;
+
+use crate::answers::Answers;
+use crate::fetch::Client;
+use crate::history;
+
+// Every answer sent is logged with the site's verdict in a tab
+// separated attempts file, which is what keeps `submit` from sending
+// an answer already known to be wrong or sending one too soon.
+
+const HEADER: &str = "timestamp_ms\tday\tpart\tanswer\tverdict\tretry_at_ms";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    Wrong,
+    TooSoon,
+    Solved,
+    Unknown,
+}
+
+const VERDICTS: [(Verdict, &str); 7] = [
+    (Verdict::Correct, "correct"),
+    (Verdict::TooHigh, "too_high"),
+    (Verdict::TooLow, "too_low"),
+    (Verdict::Wrong, "wrong"),
+    (Verdict::TooSoon, "too_soon"),
+    (Verdict::Solved, "solved"),
+    (Verdict::Unknown, "unknown"),
+];
+
+impl Verdict {
+    fn name(self) -> &'static str {
+        VERDICTS.iter().find(|(v, _)| *v == self).unwrap().1
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        VERDICTS.iter().find(|(_, n)| *n == name).map(|(v, _)| *v)
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&self.name().replace('_', " "))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Reply {
+    pub verdict: Verdict,
+    // How long the site wants us to wait before the next answer.
+    pub wait: Option<Duration>,
+    pub message: String,
+}
+
+// The text of the <article> the site puts its verdict in, without
+// markup.
+fn article_text(html: &str) -> String {
+    let article = html
+        .split_once("<article>")
+        .and_then(|(_, rest)| rest.split_once("</article>"))
+        .map_or(html, |(article, _)| article);
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in article.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => (),
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// "You have 1m 23s left to wait" after answering too soon, or "please
+// wait 5 minutes before trying again" after a wrong answer.
+fn parse_wait(text: &str) -> Option<Duration> {
+    if let Some((before, _)) = text.split_once(" left to wait") {
+        let (_, left) = before.rsplit_once("You have ")?;
+        let mut secs = 0;
+        for part in left.split_whitespace() {
+            let (n, unit) = part.split_at(part.len() - 1);
+            let n: u64 = n.parse().ok()?;
+            secs += match unit {
+                "h" => n * 3600,
+                "m" => n * 60,
+                "s" => n,
+                _ => return None,
+            };
+        }
+        return Some(Duration::from_secs(secs));
+    }
+    let (_, rest) = text.split_once("wait ")?;
+    let mut words = rest.split_whitespace();
+    let n = match words.next()? {
+        "one" => 1,
+        n => n.parse().ok()?,
+    };
+    words
+        .next()?
+        .starts_with("minute")
+        .then(|| Duration::from_secs(60 * n))
+}
+
+pub fn parse_reply(html: &str) -> Reply {
+    let message = article_text(html);
+    let verdict = if message.contains("That's the right answer") {
+        Verdict::Correct
+    } else if message.contains("That's not the right answer") {
+        if message.contains("too high") {
+            Verdict::TooHigh
+        } else if message.contains("too low") {
+            Verdict::TooLow
+        } else {
+            Verdict::Wrong
+        }
+    } else if message.contains("You gave an answer too recently") {
+        Verdict::TooSoon
+    } else if message.contains("solving the right level") {
+        Verdict::Solved
+    } else {
+        Verdict::Unknown
+    };
+    Reply {
+        verdict,
+        wait: parse_wait(&message),
+        message,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attempt {
+    pub timestamp: u64,
+    pub day: u32,
+    pub part: Part,
+    pub answer: Answer,
+    pub verdict: Verdict,
+    // When the site takes answers again, 0 if it did not say.
+    pub retry_at: u64,
+}
+
+impl Attempt {
+    fn to_row(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            self.timestamp,
+            self.day,
+            self.part,
+            self.answer,
+            self.verdict.name(),
+            self.retry_at
+        )
+    }
+
+    fn from_row(row: &str) -> Option<Self> {
+        let cols: Vec<&str> = row.split('\t').collect();
+        if cols.len() != 6 {
+            return None;
+        }
+        Some(Attempt {
+            timestamp: cols[0].parse().ok()?,
+            day: cols[1].parse().ok()?,
+            part: match cols[2] {
+                "1" => Part::One,
+                "2" => Part::Two,
+                _ => return None,
+            },
+            answer: cols[3].parse().ok()?,
+            verdict: Verdict::from_name(cols[4])?,
+            retry_at: cols[5].parse().ok()?,
+        })
+    }
+
+    // Whether this attempt shows `answer` to be wrong, directly or
+    // because a bound rules it out.
+    fn rules_out(&self, answer: &Answer) -> bool {
+        match self.verdict {
+            Verdict::Wrong => *answer == self.answer,
+            Verdict::TooHigh => *answer >= self.answer,
+            Verdict::TooLow => *answer <= self.answer,
+            _ => false,
+        }
+    }
+}
+
+pub struct Attempts {
+    path: PathBuf,
+    pub list: Vec<Attempt>,
+}
+
+impl Attempts {
+    // A missing log has no attempts yet.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let list = text
+            .lines()
+            .enumerate()
+            .filter(|(_, row)| !row.trim().is_empty() && *row != HEADER)
+            .map(|(i, row)| {
+                Attempt::from_row(row).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{}: line {}: malformed attempt", path.display(), i + 1),
+                    )
+                })
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Attempts {
+            path: path.to_path_buf(),
+            list,
+        })
+    }
+
+    pub fn append(&mut self, attempt: Attempt) -> io::Result<()> {
+        let new = !self.path.exists();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        if new {
+            writeln!(file, "{}", HEADER)?;
+        }
+        writeln!(file, "{}", attempt.to_row())?;
+        self.list.push(attempt);
+        Ok(())
+    }
+}
+
+// Why `answer` should not be sent, if anything says so: the part is
+// already solved, the site asked us to wait, or an earlier attempt
+// rules the answer out.
+pub fn refusal(
+    attempts: &Attempts,
+    answers: &Answers,
+    day: u32,
+    part: Part,
+    answer: &Answer,
+    now: u64,
+) -> Option<String> {
+    if let Some(accepted) = answers.get(day, part) {
+        return Some(if accepted == *answer {
+            format!(
+                "day {} part {} is already solved with {}",
+                day, part, answer
+            )
+        } else {
+            format!(
+                "day {} part {} was solved with {}, not {}",
+                day, part, accepted, answer
+            )
+        });
+    }
+    if let Some(retry_at) = attempts.list.iter().map(|a| a.retry_at).max() {
+        if retry_at > now {
+            let wait = Duration::from_secs((retry_at - now).div_ceil(1000));
+            return Some(format!("the site asked to wait another {:?}", wait));
+        }
+    }
+    attempts
+        .list
+        .iter()
+        .filter(|a| a.day == day && a.part == part)
+        .find(|a| a.rules_out(answer))
+        .map(|a| {
+            format!(
+                "{} is known to be wrong: {} was {}",
+                answer, a.answer, a.verdict
+            )
+        })
+}
+
+fn url_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+// Send an answer and log the site's verdict on it.
+pub fn submit(
+    client: &Client,
+    attempts: &mut Attempts,
+    year: u32,
+    day: u32,
+    part: Part,
+    answer: &Answer,
+) -> io::Result<Reply> {
+    let body = format!("level={}&answer={}", part, url_encode(&answer.to_string()));
+    let path = format!("/{}/day/{}/answer", year, day);
+    let response = client.request("POST", &path, Some(&body))?;
+    if response.status != 200 {
+        return Err(io::Error::other(format!(
+            "the server answered {}",
+            response.status
+        )));
+    }
+
+    let reply = parse_reply(&response.body);
+    let now = history::now();
+    attempts.append(Attempt {
+        timestamp: now,
+        day,
+        part,
+        answer: answer.clone(),
+        verdict: reply.verdict,
+        retry_at: reply.wait.map_or(0, |wait| now + wait.as_millis() as u64),
+    })?;
+    Ok(reply)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_reply, refusal, submit, Attempt, Attempts, Verdict};
+    use crate::answers::Answers;
+    use crate::fetch::Client;
+    use crate::http::testing::serve_once;
+    use aoc23::answer::Answer;
+    use aoc23::solution::Part;
+    use std::time::Duration;
+    use std::{env, fs, process};
+
+    fn page(text: &str) -> String {
+        format!(
+            "<html><main><article><p>{}</p></article></main></html>",
+            text
+        )
+    }
+
+    #[test]
+    fn test_parse_reply() {
+        let reply = parse_reply(&page(
+            "That's the right answer! You are <em>one gold star</em> closer.",
+        ));
+        assert_eq!(Verdict::Correct, reply.verdict);
+        assert_eq!(None, reply.wait);
+
+        let reply = parse_reply(&page(
+            "That's not the right answer; your answer is too high. \
+             Please wait one minute before trying again.",
+        ));
+        assert_eq!(Verdict::TooHigh, reply.verdict);
+        assert_eq!(Some(Duration::from_secs(60)), reply.wait);
+
+        let reply = parse_reply(&page(
+            "That's not the right answer. please wait 5 minutes before trying again.",
+        ));
+        assert_eq!(Verdict::Wrong, reply.verdict);
+        assert_eq!(Some(Duration::from_secs(300)), reply.wait);
+
+        let reply = parse_reply(&page(
+            "You gave an answer too recently; you have to wait after submitting an \
+             answer before trying again. You have 1m 23s left to wait.",
+        ));
+        assert_eq!(Verdict::TooSoon, reply.verdict);
+        assert_eq!(Some(Duration::from_secs(83)), reply.wait);
+
+        let reply = parse_reply(&page("You don't seem to be solving the right level."));
+        assert_eq!(Verdict::Solved, reply.verdict);
+    }
+
+    fn attempt(answer: u64, verdict: Verdict, retry_at: u64) -> Attempt {
+        Attempt {
+            timestamp: 1,
+            day: 7,
+            part: Part::Two,
+            answer: Answer::from(answer),
+            verdict,
+            retry_at,
+        }
+    }
+
+    #[test]
+    fn test_refusal() {
+        let mut answers = Answers::default();
+        let attempts = Attempts {
+            path: "unused".into(),
+            list: vec![
+                attempt(500, Verdict::TooHigh, 0),
+                attempt(100, Verdict::TooLow, 0),
+                attempt(300, Verdict::Wrong, 0),
+            ],
+        };
+        let check = |answers: &Answers, n: u64, now: u64| {
+            refusal(&attempts, answers, 7, Part::Two, &Answer::from(n), now)
+        };
+        assert!(check(&answers, 600, 10).is_some());
+        assert!(check(&answers, 100, 10).is_some());
+        assert!(check(&answers, 300, 10).is_some());
+        assert_eq!(None, check(&answers, 250, 10));
+
+        let attempts = Attempts {
+            path: "unused".into(),
+            list: vec![attempt(300, Verdict::Wrong, 60_000)],
+        };
+        assert!(refusal(&attempts, &answers, 7, Part::Two, &Answer::from(250u64), 10).is_some());
+        assert_eq!(
+            None,
+            refusal(
+                &attempts,
+                &answers,
+                7,
+                Part::Two,
+                &Answer::from(250u64),
+                60_000
+            )
+        );
+
+        answers.set(7, Part::Two, &Answer::from(250u64));
+        assert!(check(&answers, 250, 10).is_some());
+    }
+
+    #[test]
+    fn test_submit() {
+        let path = env::temp_dir().join(format!("aoc23-attempts-{}.tsv", process::id()));
+        let (url, server) = serve_once(
+            200,
+            &page("That's not the right answer; your answer is too low. Please wait one minute before trying again."),
+        );
+        let client = Client {
+            base_url: url,
+            session: "53cr3t".to_string(),
+            stamp: env::temp_dir().join(format!("aoc23-submit-stamp-{}", process::id())),
+            min_interval: Duration::ZERO,
+        };
+        let mut attempts = Attempts::load(&path).unwrap();
+        let reply = submit(
+            &client,
+            &mut attempts,
+            2023,
+            5,
+            Part::One,
+            &Answer::from(42u64),
+        )
+        .unwrap();
+        assert_eq!(Verdict::TooLow, reply.verdict);
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /2023/day/5/answer HTTP/1.0\r\n"));
+        assert!(request.contains("\r\nContent-Type: application/x-www-form-urlencoded\r\n"));
+        assert!(request.ends_with("\r\n\r\nlevel=1&answer=42"));
+
+        let logged = Attempts::load(&path).unwrap().list;
+        assert_eq!(attempts.list, logged);
+        assert_eq!(Verdict::TooLow, logged[0].verdict);
+        assert_eq!(logged[0].timestamp + 60_000, logged[0].retry_at);
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&client.stamp).unwrap();
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+// The subset of TOML our data and config files use: [table] headers
+// followed by `key = value` lines, where a value is a string, an
+// integer, a float or a boolean. Keys before the first header land
+// in the table named "".
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Str(s) => {
+                write!(f, "\"")?;
+                for c in s.chars() {
+                    match c {
+                        '"' => write!(f, "\\\"")?,
+                        '\\' => write!(f, "\\\\")?,
+                        '\n' => write!(f, "\\n")?,
+                        '\t' => write!(f, "\\t")?,
+                        _ => write!(f, "{}", c)?,
+                    }
+                }
+                write!(f, "\"")
+            }
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Float(n) => write!(f, "{:?}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+pub type Table = BTreeMap<String, Value>;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Document {
+    pub tables: BTreeMap<String, Table>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TomlError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TomlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+fn parse_string(s: &str) -> Option<(String, &str)> {
+    let mut out = String::new();
+    let mut chars = s.strip_prefix('"')?.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((out, &s[i + 2..])),
+            '\\' => match chars.next()?.1 {
+                '"' => out.push('"'),
+                '\\' => out.push('\\'),
+                'n' => out.push('\n'),
+                't' => out.push('\t'),
+                _ => return None,
+            },
+            _ => out.push(c),
+        }
+    }
+    None
+}
+
+fn strip_comment(s: &str) -> &str {
+    s.split_once('#').map_or(s, |(s, _)| s).trim()
+}
+
+fn parse_value(s: &str) -> Option<Value> {
+    if s.starts_with('"') {
+        let (value, rest) = parse_string(s)?;
+        return strip_comment(rest).is_empty().then_some(Value::Str(value));
+    }
+    let s = strip_comment(s);
+    let num = s.replace('_', "");
+    match s {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => num
+            .parse::<i64>()
+            .map(Value::Int)
+            .or_else(|_| num.parse::<f64>().map(Value::Float))
+            .ok(),
+    }
+}
+
+impl Document {
+    pub fn parse(text: &str) -> Result<Self, TomlError> {
+        let mut doc = Document::default();
+        let mut table = String::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let err = |message: &str| TomlError {
+                line: i + 1,
+                message: message.to_string(),
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[') {
+                let name = strip_comment(header)
+                    .strip_suffix(']')
+                    .ok_or_else(|| err("expected ']'"))?;
+                table = name.trim().to_string();
+                doc.tables.entry(table.clone()).or_default();
+                continue;
+            }
+
+            let (key, value) = line.split_once('=').ok_or_else(|| err("expected '='"))?;
+            let key = key.trim();
+            if key.is_empty() {
+                return Err(err("missing key"));
+            }
+            let value = parse_value(value.trim()).ok_or_else(|| err("invalid value"))?;
+            doc.tables
+                .entry(table.clone())
+                .or_default()
+                .insert(key.to_string(), value);
+        }
+        Ok(doc)
+    }
+
+    pub fn get(&self, table: &str, key: &str) -> Option<&Value> {
+        self.tables.get(table).and_then(|t| t.get(key))
+    }
+
+    pub fn set(&mut self, table: &str, key: &str, value: Value) {
+        self.tables
+            .entry(table.to_string())
+            .or_default()
+            .insert(key.to_string(), value);
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for (name, table) in &self.tables {
+            if !name.is_empty() {
+                if !first {
+                    writeln!(f)?;
+                }
+                writeln!(f, "[{}]", name)?;
+            }
+            for (key, value) in table {
+                writeln!(f, "{} = {}", key, value)?;
+            }
+            first = false;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Document, Value};
+
+    #[test]
+    fn test_parse() {
+        let doc = Document::parse(
+            r#"
+# comment
+data_dir = "inputs"
+
+[day2]
+red = 12 # trailing comment
+ratio = 1.5
+fast = true
+name = "say \"hi\" # not a comment"
+"#,
+        )
+        .unwrap();
+        assert_eq!(Some(&Value::Str("inputs".into())), doc.get("", "data_dir"));
+        assert_eq!(Some(&Value::Int(12)), doc.get("day2", "red"));
+        assert_eq!(Some(&Value::Float(1.5)), doc.get("day2", "ratio"));
+        assert_eq!(Some(&Value::Bool(true)), doc.get("day2", "fast"));
+        assert_eq!(
+            Some(&Value::Str("say \"hi\" # not a comment".into())),
+            doc.get("day2", "name")
+        );
+        assert_eq!(doc, Document::parse(&doc.to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(2, Document::parse("\n[day1\n").unwrap_err().line);
+        assert_eq!(1, Document::parse("part1 53194").unwrap_err().line);
+        assert_eq!(1, Document::parse("part1 = \"open").unwrap_err().line);
+        assert_eq!(1, Document::parse("part1 = twelve").unwrap_err().line);
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+
+use aoc23::answer::Answer;
+
+use crate::answers::Answers;
+use crate::runner::Outcome;
+
+pub enum Verdict {
+    Pass,
+    Fail(Answer),
+    Missing,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Pass => f.pad("PASS"),
+            Verdict::Fail(_) => f.pad("FAIL"),
+            Verdict::Missing => f.pad("MISSING"),
+        }
+    }
+}
+
+pub fn verdict(outcome: &Outcome, answers: &Answers) -> Verdict {
+    match answers.get(outcome.day, outcome.part) {
+        None => Verdict::Missing,
+        Some(expected) => match &outcome.answer {
+            Ok(answer) if *answer == expected => Verdict::Pass,
+            _ => Verdict::Fail(expected),
+        },
+    }
+}
+
+// Take the answers of parts without a recorded one as accepted.
+pub fn record_missing(outcomes: &[Outcome], answers: &mut Answers) -> usize {
+    let mut recorded = 0;
+    for o in outcomes {
+        if let (Verdict::Missing, Ok(answer)) = (verdict(o, answers), &o.answer) {
+            answers.set(o.day, o.part, answer);
+            recorded += 1;
+        }
+    }
+    recorded
+}
+
+// Print a line per part and tell whether every recorded answer held.
+pub fn report(outcomes: &[Outcome], answers: &Answers) -> bool {
+    let mut passed = true;
+    for o in outcomes {
+        let got = match &o.answer {
+            Ok(answer) => answer.to_string(),
+            Err(e) => format!("<{}>", e),
+        };
+        let verdict = verdict(o, answers);
+        match &verdict {
+            Verdict::Pass => println!("day {:>2} part {}  {:<7}  {}", o.day, o.part, verdict, got),
+            Verdict::Missing => println!(
+                "day {:>2} part {}  {:<7}  got {}",
+                o.day, o.part, verdict, got
+            ),
+            Verdict::Fail(expected) => {
+                passed = false;
+                println!("day {:>2} part {}  {:<7}", o.day, o.part, verdict);
+                println!("    - {}", expected);
+                println!("    + {}", got);
+            }
+        }
+    }
+    passed
+}
@@ -0,0 +1,235 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, SystemTime};
+use std::{fs, io};
+
+use aoc23::registry;
+
+use crate::{runner, scaffold};
+
+// `watch` polls the source and input of a day and, whenever one of them
+// changes, rebuilds through cargo, runs the day's tests and solves it
+// with the fresh binary, comparing the answers with the previous round.
+
+const POLL: Duration = Duration::from_millis(500);
+
+// Input, part and answer of one solved part.
+type Row = (String, String, String);
+
+// When a file was last modified, or the newest file in a directory.
+fn modified(path: &Path) -> Option<SystemTime> {
+    let meta = fs::metadata(path).ok()?;
+    if !meta.is_dir() {
+        return meta.modified().ok();
+    }
+    fs::read_dir(path)
+        .ok()?
+        .filter_map(|entry| modified(&entry.ok()?.path()))
+        .chain(meta.modified().ok())
+        .max()
+}
+
+fn mtimes(paths: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    paths.iter().map(|p| modified(p)).collect()
+}
+
+// Split a line of our own CSV output, where fields with commas or
+// quotes are quoted.
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+// The (input, part, answer) of every row `run --format csv` printed,
+// with failures shown as their status. Columns are found by their name
+// in the header, so that new ones do not shift them.
+fn parse_results(csv: &str) -> Vec<Row> {
+    let mut rows = csv.lines().map(csv_fields);
+    let header = rows.next().unwrap_or_default();
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let (Some(part), Some(status), Some(answer), Some(input)) = (
+        column("part"),
+        column("status"),
+        column("answer"),
+        column("input"),
+    ) else {
+        return vec![];
+    };
+    rows.filter(|f| f.len() == header.len())
+        .map(|f| {
+            let answer = if f[status] == "ok" {
+                f[answer].clone()
+            } else {
+                f[status].to_uppercase()
+            };
+            (f[input].clone(), f[part].clone(), answer)
+        })
+        .collect()
+}
+
+fn diff(previous: &[Row], current: &[Row]) -> Vec<String> {
+    current
+        .iter()
+        .map(|(input, part, answer)| {
+            let before = previous
+                .iter()
+                .find(|(i, p, _)| i == input && p == part)
+                .map(|(_, _, a)| a);
+            let change = match before {
+                None => String::new(),
+                Some(before) if before == answer => "  (unchanged)".to_string(),
+                Some(before) => format!("  (was {})", before),
+            };
+            format!("{}  part {}  {}{}", input, part, answer, change)
+        })
+        .collect()
+}
+
+fn cargo(args: &[&str]) -> io::Result<std::process::Output> {
+    Command::new("cargo").args(args).output()
+}
+
+// One round: build, test and solve. Returns the results if the day got
+// that far.
+fn round(year: u32, day: u32, example: bool) -> io::Result<Option<Vec<Row>>> {
+    let build = cargo(&["build", "--quiet"])?;
+    if !build.status.success() {
+        eprint!("{}", String::from_utf8_lossy(&build.stderr));
+        println!("build failed");
+        return Ok(None);
+    }
+
+    let filter = if year == registry::DEFAULT_YEAR {
+        format!("day{}::", day)
+    } else {
+        format!("y{}::day{}::", year, day)
+    };
+    let tests = cargo(&["test", "--quiet", "--lib", "--", &filter])?;
+    let out = String::from_utf8_lossy(&tests.stdout);
+    if tests.status.success() {
+        let summary = out.lines().find(|l| l.starts_with("test result:"));
+        println!(
+            "tests: {}",
+            summary.map_or("ok", |l| &l["test result: ".len()..])
+        );
+    } else {
+        print!("{}", out);
+        println!("tests failed");
+    }
+
+    let (year, day) = (year.to_string(), day.to_string());
+    let mut run = vec![
+        "run", "--quiet", "--", "--year", &year, "run", "--day", &day, "--format", "csv",
+    ];
+    if example {
+        run.push("--example");
+    }
+    let solve = cargo(&run)?;
+    eprint!("{}", String::from_utf8_lossy(&solve.stderr));
+    Ok(Some(parse_results(&String::from_utf8_lossy(&solve.stdout))))
+}
+
+pub fn watch(year: u32, day: u32, example: bool) -> io::Result<()> {
+    let mut paths = vec![PathBuf::from(scaffold::module_path(year, day))];
+    if example {
+        paths.push(PathBuf::from(runner::examples_dir(year, day)));
+    } else {
+        paths.push(PathBuf::from(runner::data_path(year, day)));
+    }
+    println!(
+        "watching {}",
+        paths
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" and ")
+    );
+
+    let mut seen = None;
+    let mut previous = vec![];
+    loop {
+        let now = mtimes(&paths);
+        if seen.as_ref() != Some(&now) {
+            seen = Some(now);
+            println!();
+            if let Some(results) = round(year, day, example)? {
+                for line in diff(&previous, &results) {
+                    println!("{}", line);
+                }
+                previous = results;
+            }
+        }
+        thread::sleep(POLL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{csv_fields, diff, parse_results};
+    use crate::format;
+    use crate::runner::{Failure, Outcome};
+    use aoc23::answer::Answer;
+    use aoc23::solution::Part;
+    use std::time::Duration;
+
+    // The csv `run` prints for day 7 on data/day7.
+    fn results(answers: [Result<Answer, Failure>; 2]) -> String {
+        let outcomes: Vec<Outcome> = Part::BOTH
+            .into_iter()
+            .zip(answers)
+            .map(|(part, answer)| Outcome {
+                year: 2023,
+                day: 7,
+                part,
+                input: "data/day7".to_string(),
+                input_hash: 0xab,
+                answer,
+                elapsed: Duration::from_nanos(10),
+            })
+            .collect();
+        format::csv(&outcomes)
+    }
+
+    #[test]
+    fn test_csv_fields() {
+        assert_eq!(vec!["7", "2", "ok"], csv_fields("7,2,ok"));
+        assert_eq!(vec!["a \"b\", c", ""], csv_fields("\"a \"\"b\"\", c\","));
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = parse_results(&results([
+            Ok(Answer::from(6440u64)),
+            Err(Failure::Panicked("not yet implemented".to_string())),
+        ]));
+        let after = parse_results(&results([
+            Ok(Answer::from(6440u64)),
+            Ok(Answer::from(5905u64)),
+        ]));
+        assert_eq!(
+            vec![
+                "data/day7  part 1  6440  (unchanged)",
+                "data/day7  part 2  5905  (was PANIC)",
+            ],
+            diff(&before, &after)
+        );
+        assert_eq!(
+            vec!["data/day7  part 1  6440", "data/day7  part 2  PANIC"],
+            diff(&[], &before)
+        );
+    }
+}
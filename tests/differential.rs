@@ -0,0 +1,24 @@
+// Checks the days that have a naive reference against it, on a few
+// thousand generated inputs between them.
+
+use aoc23::differential;
+use aoc23::registry;
+
+#[test]
+fn test_against_references() {
+    let mut checked = 0;
+    let mut failures = vec![];
+
+    for entry in registry::all() {
+        let solver = entry.solver;
+        let Some(summary) = differential::run(solver.year(), solver.day(), 0, 500, 20) else {
+            continue;
+        };
+        checked += summary.checked;
+        failures.extend(summary.unparsed.iter().map(|u| u.to_string()));
+        failures.extend(summary.mismatches.iter().map(|m| m.to_string()));
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+    assert!(checked > 1000, "only {} parts checked", checked);
+}
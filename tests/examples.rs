@@ -0,0 +1,114 @@
+// Solves every example under examples/<year>/day<n> (examples/day<n>
+// for 2023) through the registry and
+// checks the answers given in the .toml file next to it:
+//
+//     part1 = 6440
+//     part2 = 5905
+//     part2_pending = true
+//
+// A part without an answer is not checked. A pending part is one that
+// is not solved yet; it fails the test once it is, so that the flag
+// gets dropped.
+
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+use aoc23::answer::Answer;
+use aoc23::registry;
+use aoc23::solution::{Part, Solver};
+use aoc23::toml::{Document, Value};
+
+fn examples(solver: &dyn Solver) -> Vec<PathBuf> {
+    let day = format!("day{}", solver.day());
+    let dir = registry::year_path(Path::new("examples"), solver.year(), &day);
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn expected(value: &Value) -> Answer {
+    match value {
+        Value::Int(n) => Answer::from(*n),
+        Value::Str(s) => s.parse().unwrap(),
+        _ => panic!("expected an answer, got {}", value),
+    }
+}
+
+fn solve(solver: &dyn Solver, input: &str, part: Part) -> Result<Answer, String> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let model = solver.parse(input)?;
+        solver.solve(model.as_ref(), part)
+    }));
+    match result {
+        Ok(Ok(answer)) => Ok(answer),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err("panicked".to_string()),
+    }
+}
+
+#[test]
+fn test_examples_lint_clean() {
+    let mut problems = vec![];
+    for entry in registry::all() {
+        for path in examples(entry.solver) {
+            let input = fs::read_to_string(&path).unwrap();
+            problems.extend(
+                entry
+                    .linter
+                    .lint(&input)
+                    .iter()
+                    .map(|e| format!("{}: {}", path.display(), e)),
+            );
+        }
+    }
+    assert!(problems.is_empty(), "\n{}", problems.join("\n"));
+}
+
+#[test]
+fn test_examples() {
+    let mut checked = 0;
+    let mut failures = vec![];
+
+    for entry in registry::all() {
+        let solver = entry.solver;
+        for path in examples(solver) {
+            let sidecar = path.with_extension("toml");
+            let Ok(text) = fs::read_to_string(&sidecar) else {
+                continue;
+            };
+            let doc =
+                Document::parse(&text).unwrap_or_else(|e| panic!("{}: {}", sidecar.display(), e));
+            let input = fs::read_to_string(&path).unwrap();
+
+            for part in Part::BOTH {
+                let key = format!("part{}", part);
+                let Some(value) = doc.get("", &key) else {
+                    continue;
+                };
+                let want = expected(value);
+                let pending = doc.get("", &format!("{}_pending", key)) == Some(&Value::Bool(true));
+                let name = format!("{} part {}", path.display(), part);
+
+                match solve(solver, &input, part) {
+                    Ok(got) if got == want && pending => {
+                        failures.push(format!("{}: solved, drop {}_pending", name, key))
+                    }
+                    Ok(got) if got == want => checked += 1,
+                    _ if pending => (),
+                    Ok(got) => failures.push(format!("{}: expected {}, got {}", name, want, got)),
+                    Err(e) => failures.push(format!("{}: expected {}, {}", name, want, e)),
+                }
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+    assert!(checked > 0, "no examples with answers");
+}